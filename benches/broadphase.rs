@@ -0,0 +1,91 @@
+// Compares the collision pass of the game with and without the broad phase
+// grid, for growing numbers of bullets.
+//
+// Run with `cargo bench --bench broadphase`.
+
+extern crate sdl2;
+extern crate rand;
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/phi/data.rs"]
+mod data;
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/phi/random.rs"]
+mod random;
+
+use data::{Grid, Rectangle};
+use random::Random;
+use std::time::Instant;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 600.0;
+const ASTEROIDS: usize = 50;
+const ASTEROID_SIDE: f64 = 96.0;
+const FRAMES: usize = 60;
+// Every run places the same rectangles, so that runs can be compared
+const SEED: u64 = 42;
+
+fn random_rect(rng: &mut Random, w: f64, h: f64) -> Rectangle {
+    Rectangle {
+        x: rng.range(0.0, WIDTH - w),
+        y: rng.range(0.0, HEIGHT - h),
+        w,
+        h,
+    }
+}
+
+// Tests every asteroid against every bullet
+fn naive(asteroids: &[Rectangle], bullets: &[Rectangle]) -> usize {
+    asteroids.iter()
+        .map(|asteroid| bullets.iter().filter(|bullet| asteroid.overlaps(**bullet)).count())
+        .sum()
+}
+
+// Fills the grid with the bullets, then tests every asteroid against the
+// bullets near it
+fn grid(grid: &mut Grid<usize>, asteroids: &[Rectangle], bullets: &[Rectangle]) -> usize {
+    grid.clear();
+    for (i, bullet) in bullets.iter().enumerate() {
+        grid.insert(*bullet, i);
+    }
+
+    let mut hits = 0;
+    for asteroid in asteroids {
+        grid.query(*asteroid, |&i| if asteroid.overlaps(bullets[i]) { hits += 1; });
+    }
+    hits
+}
+
+// The average duration of a frame, in microseconds
+fn time<F: FnMut() -> usize>(mut pass: F) -> (f64, usize) {
+    let start = Instant::now();
+    let mut hits = 0;
+    for _ in 0..FRAMES {
+        hits = pass();
+    }
+    let elapsed = start.elapsed();
+    let micros = elapsed.as_secs() as f64 * 1e6 + elapsed.subsec_nanos() as f64 / 1e3;
+    (micros / FRAMES as f64, hits)
+}
+
+fn main() {
+    let mut rng = Random::new(SEED);
+    let asteroids: Vec<_> = (0..ASTEROIDS)
+        .map(|_| random_rect(&mut rng, ASTEROID_SIDE, ASTEROID_SIDE))
+        .collect();
+    let mut cells = Grid::new(ASTEROID_SIDE);
+
+    println!("{:>8} {:>12} {:>12} {:>8}", "bullets", "naive (us)", "grid (us)", "speedup");
+
+    for &count in &[100, 1000, 5000, 20000] {
+        let bullets: Vec<_> = (0..count).map(|_| random_rect(&mut rng, 8.0, 4.0)).collect();
+
+        let (naive_time, naive_hits) = time(|| naive(&asteroids, &bullets));
+        let (grid_time, grid_hits) = time(|| grid(&mut cells, &asteroids, &bullets));
+        assert_eq!(naive_hits, grid_hits, "the grid missed some collisions");
+
+        println!("{:>8} {:>12.1} {:>12.1} {:>7.1}x",
+                 count, naive_time, grid_time, naive_time / grid_time);
+    }
+}
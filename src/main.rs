@@ -6,9 +6,160 @@ extern crate rand;
 mod phi;
 mod views;
 
+// Where the player's controls are kept between runs. They go in the user's
+// configuration directory, as the game may be started from anywhere.
+fn controls_path() -> Option<::std::path::PathBuf> {
+    use ::std::env::var_os;
+    use ::std::path::PathBuf;
+
+    let config_dir = var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("arcade-rs").join("controls.cfg"))
+}
+
+// Reads the player's controls, or writes the default ones so that they can be
+// edited by hand. Invalid controls are reported but do not stop the game.
+fn load_controls(phi: &mut ::phi::Phi) {
+    let path = match controls_path() {
+        Some(path) => path,
+        None => return,
+    };
+    let path_str = match path.to_str() {
+        Some(path) => path,
+        None => return,
+    };
+
+    if path.exists() {
+        match ::phi::input::Bindings::load(path_str) {
+            Ok(bindings) => phi.events.bindings = bindings,
+            Err(e) => println!("Using the default controls: {}", e),
+        }
+        return;
+    }
+
+    if let Some(dir) = path.parent() {
+        if let Err(e) = ::std::fs::create_dir_all(dir) {
+            println!("Could not save the controls: {}", e);
+            return;
+        }
+    }
+    if let Err(e) = phi.events.bindings.save(path_str) {
+        println!("Could not save the controls: {}", e);
+    }
+}
+
+// Returns the value which follows `name` on the command line, if any
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|value| &value[..])
+}
+
+// Records or plays back the run, as asked on the command line. This must
+// happen before the views are created, so that they see the seed of the replay.
+fn setup_replay(phi: &mut ::phi::Phi, args: &[String]) -> ::phi::error::PhiResult<()> {
+    if let Some(path) = option(args, "--replay") {
+        phi.play_replay(::phi::replay::Replay::load(path)?);
+    }
+
+    if let Some(path) = option(args, "--record") {
+        phi.start_recording(path);
+    }
+
+    Ok(())
+}
+
+// Plays the game without a player: starts a game from the main menu, then
+// flies up and down while firing, so that the smoke test exercises `GameView`
+fn autopilot() -> impl FnMut(f64) -> Option<::phi::replay::Frame> {
+    use ::phi::input::InputEvent::{KeyDown, KeyUp};
+    use ::sdl2::keyboard::Keycode::{Down, Return, Space, Up};
+
+    let mut step = 0;
+    move |elapsed| {
+        step += 1;
+
+        let events = match step {
+            1 => vec![KeyDown { key: Return, repeat: false }],
+            2 => vec![KeyUp(Return)],
+            _ => {
+                let mut events = Vec::new();
+                match step % 30 {
+                    0 => events.push(KeyDown { key: Space, repeat: false }),
+                    1 => events.push(KeyUp(Space)),
+                    _ => {},
+                }
+                match step % 240 {
+                    0 => events.extend(vec![KeyUp(Down), KeyDown { key: Up, repeat: false }]),
+                    120 => events.extend(vec![KeyUp(Up), KeyDown { key: Down, repeat: false }]),
+                    _ => {},
+                }
+                events
+            },
+        };
+
+        Some(::phi::replay::Frame { elapsed, events })
+    }
+}
+
 fn main() {
-    ::phi::spawn("ArcadeRS Shooter", |phi| {
-        Box::new(::views::main_menu::MainMenuView::new(phi))
+    let args: Vec<String> = ::std::env::args().collect();
+
+    // `--headless <frames>` runs the game without opening a window, which is
+    // used as a smoke test on machines without a display. The game is then
+    // played by `autopilot`, unless a replay is given. `--record <path>` and
+    // `--replay <path>` save the inputs of a run and play them back, and
+    // `--fullscreen` changes the resolution of the screen to that of the game.
+    if let Some(frames) = option(&args, "--headless") {
+        let frames = match frames.parse() {
+            Ok(frames) => frames,
+            Err(e) => {
+                println!("Expected a number of frames after --headless: {}", e);
+                ::std::process::exit(1);
+            },
+        };
+
+        let result = ::phi::headless::Headless::new(800, 600, |phi| {
+            phi.events.set_source(Box::new(autopilot()));
+            setup_replay(phi, &args)?;
+            Ok(Box::new(::views::main_menu::MainMenuView::new(phi)?))
+        });
+
+        match result {
+            Ok(mut headless) => { headless.step(frames, ::phi::UPDATE_INTERVAL); },
+            Err(e) => {
+                println!("Could not start the game: {}", e);
+                ::std::process::exit(1);
+            },
+        }
+        return;
+    }
+
+    // Every player sees the same game area, whatever the size of the window
+    let mode = if args.iter().any(|arg| arg == "--fullscreen") {
+        ::phi::window::WindowMode::Fullscreen
+    } else {
+        ::phi::window::WindowMode::Windowed
+    };
+
+    let config = ::phi::window::WindowConfig {
+        mode,
+        logical_size: Some((800, 600)),
+        ..::phi::window::WindowConfig::new("ArcadeRS Shooter")
+    };
+
+    let result = ::phi::spawn(config, |phi| {
+        phi.enable_stats_overlay("assets/belligerent.ttf");
+        load_controls(phi);
+        setup_replay(phi, &args)?;
+        Ok(Box::new(::views::main_menu::MainMenuView::new(phi)?))
     });
 
+    if let Err(e) = result {
+        println!("Could not start the game: {}", e);
+        ::std::process::exit(1);
+    }
 }
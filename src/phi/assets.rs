@@ -0,0 +1,124 @@
+use ::phi::error::{PhiError, PhiResult};
+use ::phi::gfx::{AnimatedSprite, AnimatedSpriteDescr, Sprite};
+use ::sdl2::render::Renderer;
+use ::sdl2_ttf::Font;
+use ::std::collections::HashMap;
+use ::std::path::Path;
+use ::std::rc::Rc;
+
+/// Keeps the images and fonts used by the game in memory, so that each of them
+/// is only loaded from the disk once. Assets are identified by their path.
+///
+/// The assets are shared through reference counting, so every sprite which
+/// was loaded from the same image uses the same texture.
+pub struct AssetManager {
+    sprites: HashMap<String, Sprite>,
+    frames: HashMap<FramesKey, Rc<Vec<Sprite>>>,
+    fonts: HashMap<(String, i32), Rc<Font>>,
+}
+
+// The frames of an animation depend on the sheet, and on how it is split
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FramesKey {
+    path: String,
+    total_frames: usize,
+    frames_high: usize,
+    frames_wide: usize,
+    // The bits of the frame size, as floats cannot be hashed
+    frame_w: u64,
+    frame_h: u64,
+}
+
+impl FramesKey {
+    fn new(descr: &AnimatedSpriteDescr) -> FramesKey {
+        FramesKey {
+            path: descr.image_path.to_string(),
+            total_frames: descr.total_frames,
+            frames_high: descr.frames_high,
+            frames_wide: descr.frames_wide,
+            frame_w: descr.frame_w.to_bits(),
+            frame_h: descr.frame_h.to_bits(),
+        }
+    }
+}
+
+impl AssetManager {
+    pub fn new() -> AssetManager {
+        AssetManager {
+            sprites: HashMap::new(),
+            frames: HashMap::new(),
+            fonts: HashMap::new(),
+        }
+    }
+
+    /// Returns the image at `path` as a sprite, loading it if necessary.
+    pub fn sprite(&mut self, renderer: &Renderer, path: &str) -> PhiResult<Sprite> {
+        if let Some(sprite) = self.sprites.get(path) {
+            return Ok(sprite.clone());
+        }
+
+        let sprite = Sprite::load(renderer, path)?;
+        self.sprites.insert(path.to_string(), sprite.clone());
+        Ok(sprite)
+    }
+
+    /// Returns the animation stored in the sprite sheet described by `descr`,
+    /// loading it if necessary. Every animation loaded from the same sheet with
+    /// the same layout shares its frames, but has its own timing.
+    pub fn animation(&mut self, renderer: &Renderer, descr: AnimatedSpriteDescr, fps: f64) -> PhiResult<AnimatedSprite> {
+        let key = FramesKey::new(&descr);
+
+        if let Some(frames) = self.frames.get(&key) {
+            return AnimatedSprite::from_shared(frames.clone(), fps);
+        }
+
+        let spritesheet = self.sprite(renderer, descr.image_path)?;
+        let frames = Rc::new(AnimatedSprite::split_frames(&spritesheet, &descr)?);
+        self.frames.insert(key, frames.clone());
+        AnimatedSprite::from_shared(frames, fps)
+    }
+
+    /// Returns the font at `path` with the given point size, loading it if
+    /// necessary.
+    pub fn font(&mut self, path: &str, size: i32) -> PhiResult<Rc<Font>> {
+        let key = (path.to_string(), size);
+
+        if let Some(font) = self.fonts.get(&key) {
+            return Ok(font.clone());
+        }
+
+        let font = Rc::new(Font::from_file(Path::new(path), size)
+            .map_err(|e| PhiError::Font {
+                path: path.to_string(),
+                reason: e.0,
+            })?);
+
+        self.fonts.insert(key, font.clone());
+        Ok(font)
+    }
+
+    /// Loads every image in `paths` ahead of time, for example before showing
+    /// a view which needs them.
+    pub fn preload(&mut self, renderer: &Renderer, paths: &[&str]) -> PhiResult<()> {
+        for path in paths {
+            self.sprite(renderer, path)?;
+        }
+        Ok(())
+    }
+
+    /// The number of images and of fonts currently loaded.
+    pub fn len(&self) -> (usize, usize) {
+        (self.sprites.len(), self.fonts.len())
+    }
+
+    /// An estimate of the memory used by the loaded images, in bytes. Every
+    /// pixel is assumed to take four bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.sprites.values()
+            .map(|sprite| {
+                let (w, h) = sprite.size();
+                w as usize * h as usize * 4
+            })
+            .sum()
+    }
+}
@@ -0,0 +1,485 @@
+use ::phi::Phi;
+use ::phi::data::{Rectangle, Vec2};
+use ::phi::error::{PhiError, PhiResult};
+use ::phi::gfx::{CopySprite, Sprite};
+use ::sdl2::pixels::{Color, PixelFormatEnum};
+use ::std::rc::Rc;
+
+/// Pixels whose alpha is at least this value are solid.
+const ALPHA_THRESHOLD: u8 = 128;
+
+/// The pixels of a sprite which are solid enough to collide with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlphaMask {
+    width: usize,
+    height: usize,
+    solid: Vec<bool>,
+}
+
+impl AlphaMask {
+    /// Reads back the pixels of `sprite` by drawing it to an offscreen texture.
+    /// This is slow, and so should be done while loading rather than every
+    /// frame.
+    pub fn from_sprite(phi: &mut Phi, sprite: &Sprite) -> PhiResult<AlphaMask> {
+        let (w, h) = sprite.size();
+        let (width, height) = (w as usize, h as usize);
+        let bounds = Rectangle::with_size(w, h);
+
+        let pixels = {
+            let renderer = &mut phi.renderer;
+
+            renderer.render_target()
+                .ok_or_else(|| PhiError::Sdl("Render targets are not supported".to_string()))?
+                .create_and_set(PixelFormatEnum::ARGB8888, (width as u32, height as u32))?;
+
+            // The logical resolution of the game does not apply to the texture
+            renderer.set_scale(1.0, 1.0);
+            renderer.set_viewport(None);
+            renderer.set_clip_rect(None);
+
+            renderer.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            renderer.clear();
+            renderer.copy_sprite(sprite, bounds);
+            let pixels = renderer.read_pixels(bounds.to_sdl(), PixelFormatEnum::ARGB8888);
+
+            // Dropping the texture which was drawn to
+            if let Some(mut target) = renderer.render_target() {
+                target.reset()?;
+            }
+            pixels?
+        };
+        phi.update_viewport()?;
+
+        Ok(AlphaMask {
+            width,
+            height,
+            solid: pixels.chunks(4)
+                .map(|pixel| {
+                    // Packed pixels are stored in the native byte order
+                    let argb = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                    (argb >> 24) as u8 >= ALPHA_THRESHOLD
+                })
+                .collect(),
+        })
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Whether the pixel at (`x`, `y`) is solid. Pixels outside of the mask
+    /// are not.
+    pub fn is_solid(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.solid[y * self.width + x]
+    }
+}
+
+
+/// The shape with which an entity collides. Every shape can be tested against
+/// every other one. Shapes which only touch each other overlap.
+#[derive(Clone, Debug)]
+pub enum Collider {
+    Rect(Rectangle),
+    Circle { center: Vec2, radius: f64 },
+    /// The points within `radius` of the segment from `a` to `b`.
+    Capsule { a: Vec2, b: Vec2, radius: f64 },
+    /// A convex polygon, whose points may be given in either order. With
+    /// fewer than three points, it is a single point or a segment, and
+    /// without any point it collides with nothing.
+    Polygon(Vec<Vec2>),
+    /// An alpha mask stretched over `rect`.
+    Mask { mask: Rc<AlphaMask>, rect: Rectangle },
+}
+
+// The primitive shapes to which every collider reduces
+enum Shape<'a> {
+    // A circle is a capsule whose ends are the same point
+    Capsule(Vec2, Vec2, f64),
+    // A polygon of at least three points
+    Polygon(Vec<Vec2>),
+    Mask(&'a AlphaMask, Rectangle),
+    Empty,
+}
+
+impl Collider {
+    /// The smallest rectangle which contains the whole shape.
+    pub fn bounds(&self) -> Rectangle {
+        match *self {
+            Collider::Rect(rect) | Collider::Mask { rect, .. } => rect,
+            Collider::Circle { center, radius } =>
+                Rectangle::with_size(radius * 2.0, radius * 2.0).center_at(center),
+            Collider::Capsule { a, b, radius } => {
+                let end = Rectangle::with_size(radius * 2.0, radius * 2.0);
+                end.center_at(a).union(end.center_at(b))
+            },
+            Collider::Polygon(ref points) => points_bounds(points),
+        }
+    }
+
+    fn shape(&self) -> Shape<'_> {
+        match *self {
+            Collider::Rect(rect) => Shape::Polygon(rect.corners().to_vec()),
+            Collider::Circle { center, radius } => Shape::Capsule(center, center, radius),
+            Collider::Capsule { a, b, radius } => Shape::Capsule(a, b, radius),
+            Collider::Polygon(ref points) => match points.len() {
+                0 => Shape::Empty,
+                1 => Shape::Capsule(points[0], points[0], 0.0),
+                2 => Shape::Capsule(points[0], points[1], 0.0),
+                _ => Shape::Polygon(points.clone()),
+            },
+            Collider::Mask { ref mask, rect } => Shape::Mask(mask, rect),
+        }
+    }
+
+    /// Whether `point` lies inside of the shape.
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        match self.shape() {
+            Shape::Capsule(a, b, radius) =>
+                point.distance(closest_on_segment(point, a, b)) <= radius,
+            Shape::Polygon(points) => polygon_contains(&points, point),
+            Shape::Mask(mask, rect) => {
+                if !rect.contains_point(point) {
+                    return false;
+                }
+                let (w, h) = mask.size();
+                let x = (point.x - rect.x) / rect.w * w as f64;
+                let y = (point.y - rect.y) / rect.h * h as f64;
+                mask.is_solid(x as usize, y as usize)
+            },
+            Shape::Empty => false,
+        }
+    }
+
+    /// Whether the two shapes share at least one point.
+    pub fn overlaps(&self, other: &Collider) -> bool {
+        if !bounds_touch(self.bounds(), other.bounds()) {
+            return false;
+        }
+
+        // The bounds of rectangles are the rectangles themselves. This is
+        // tested first, so that rectangles are not turned into polygons.
+        if self.is_rect() && other.is_rect() {
+            return true;
+        }
+
+        match (self.shape(), other.shape()) {
+            (Shape::Empty, _) | (_, Shape::Empty) => false,
+
+            (Shape::Capsule(a1, b1, r1), Shape::Capsule(a2, b2, r2)) =>
+                segment_distance(a1, b1, a2, b2) <= r1 + r2,
+
+            (Shape::Capsule(a, b, radius), Shape::Polygon(points)) |
+            (Shape::Polygon(points), Shape::Capsule(a, b, radius)) =>
+                segment_polygon_distance(a, b, &points) <= radius,
+
+            (Shape::Polygon(first), Shape::Polygon(second)) =>
+                polygons_overlap(&first, &second),
+
+            (Shape::Mask(mask, rect), _) => mask_overlaps(mask, rect, other),
+            (_, Shape::Mask(mask, rect)) => mask_overlaps(mask, rect, self),
+        }
+    }
+
+    fn is_rect(&self) -> bool {
+        matches!(*self, Collider::Rect(_))
+    }
+}
+
+
+// Unlike `Rectangle::overlaps`, rectangles which only touch each other count
+fn bounds_touch(first: Rectangle, second: Rectangle) -> bool {
+    first.left() <= second.right() && second.left() <= first.right() &&
+    first.top() <= second.bottom() && second.top() <= first.bottom()
+}
+
+
+// The smallest rectangle which contains every point, which is empty at the
+// origin if there is none
+fn points_bounds(points: &[Vec2]) -> Rectangle {
+    let first = match points.first() {
+        Some(&first) => first,
+        None => return Rectangle::with_size(0.0, 0.0),
+    };
+    let (mut min, mut max) = (first, first);
+    for point in points {
+        min = Vec2::new(min.x.min(point.x), min.y.min(point.y));
+        max = Vec2::new(max.x.max(point.x), max.y.max(point.y));
+    }
+    Rectangle { x: min.x, y: min.y, w: max.x - min.x, h: max.y - min.y }
+}
+
+
+// The point of the segment from `a` to `b` which is closest to `point`
+fn closest_on_segment(point: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let ab = b - a;
+    let length = ab.length_squared();
+    if length == 0.0 {
+        return a;
+    }
+
+    let t = ((point - a).dot(ab) / length).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+fn segments_intersect(a1: Vec2, b1: Vec2, a2: Vec2, b2: Vec2) -> bool {
+    let d1 = (b1 - a1).cross(a2 - a1);
+    let d2 = (b1 - a1).cross(b2 - a1);
+    let d3 = (b2 - a2).cross(a1 - a2);
+    let d4 = (b2 - a2).cross(b1 - a2);
+
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+fn segment_distance(a1: Vec2, b1: Vec2, a2: Vec2, b2: Vec2) -> f64 {
+    if segments_intersect(a1, b1, a2, b2) {
+        return 0.0;
+    }
+
+    // Otherwise, the closest points include an end of either segment
+    [
+        a1.distance(closest_on_segment(a1, a2, b2)),
+        b1.distance(closest_on_segment(b1, a2, b2)),
+        a2.distance(closest_on_segment(a2, a1, b1)),
+        b2.distance(closest_on_segment(b2, a1, b1)),
+    ].iter().cloned().fold(f64::INFINITY, f64::min)
+}
+
+// The edges of a polygon, as pairs of consecutive points
+fn edges<'a>(points: &'a [Vec2]) -> impl Iterator<Item=(Vec2, Vec2)> + 'a {
+    points.iter().cloned().zip(points.iter().cloned().cycle().skip(1))
+}
+
+fn polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+    // Inside of a convex polygon, the point lies on the same side of every
+    // edge, whichever the order of the points
+    let mut side = 0.0;
+    for (a, b) in edges(points) {
+        let cross = (b - a).cross(point - a);
+        if cross * side < 0.0 {
+            return false;
+        }
+        if cross != 0.0 {
+            side = cross;
+        }
+    }
+
+    // Without any area, the polygon is a point or a segment, and `point` lies
+    // on its line. It must then also lie between its ends.
+    side != 0.0 || bounds_touch(points_bounds(points), Rectangle { x: point.x, y: point.y, w: 0.0, h: 0.0 })
+}
+
+fn segment_polygon_distance(a: Vec2, b: Vec2, points: &[Vec2]) -> f64 {
+    if polygon_contains(points, a) || polygon_contains(points, b) {
+        return 0.0;
+    }
+
+    edges(points)
+        .map(|(start, end)| segment_distance(a, b, start, end))
+        .fold(f64::INFINITY, f64::min)
+}
+
+// Separating axis theorem: two convex polygons are apart if and only if their
+// projections on the normal of one of their edges are apart
+fn polygons_overlap(first: &[Vec2], second: &[Vec2]) -> bool {
+    let project = |points: &[Vec2], axis: Vec2| {
+        points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), point| {
+            let projected = point.dot(axis);
+            (min.min(projected), max.max(projected))
+        })
+    };
+
+    edges(first).chain(edges(second)).all(|(a, b)| {
+        let axis = (b - a).perpendicular();
+        let (min1, max1) = project(first, axis);
+        let (min2, max2) = project(second, axis);
+        min1 <= max2 && min2 <= max1
+    })
+}
+
+// Tests every solid pixel of the mask which lies over `other`
+fn mask_overlaps(mask: &AlphaMask, rect: Rectangle, other: &Collider) -> bool {
+    let (w, h) = mask.size();
+    let (pixel_w, pixel_h) = (rect.w / w as f64, rect.h / h as f64);
+
+    // Only the pixels inside of the bounds of `other` may overlap it
+    let area = match rect.intersection(other.bounds()) {
+        Some(area) => area,
+        None => return false,
+    };
+    let first_x = ((area.x - rect.x) / pixel_w).floor().max(0.0) as usize;
+    let first_y = ((area.y - rect.y) / pixel_h).floor().max(0.0) as usize;
+    let last_x = (((area.right() - rect.x) / pixel_w).ceil().max(0.0) as usize).min(w);
+    let last_y = (((area.bottom() - rect.y) / pixel_h).ceil().max(0.0) as usize).min(h);
+
+    (first_y..last_y).any(|y| (first_x..last_x).any(|x| {
+        mask.is_solid(x, y) && other.contains_point(Vec2::new(
+            rect.x + (x as f64 + 0.5) * pixel_w,
+            rect.y + (y as f64 + 0.5) * pixel_h))
+    }))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> Collider {
+        Collider::Rect(Rectangle { x, y, w, h })
+    }
+
+    fn circle(x: f64, y: f64, radius: f64) -> Collider {
+        Collider::Circle { center: Vec2::new(x, y), radius }
+    }
+
+    fn capsule(a: (f64, f64), b: (f64, f64), radius: f64) -> Collider {
+        Collider::Capsule { a: a.into(), b: b.into(), radius }
+    }
+
+    fn polygon(points: &[(f64, f64)]) -> Collider {
+        Collider::Polygon(points.iter().map(|&point| point.into()).collect())
+    }
+
+    // A 2 x 2 mask whose top-left pixel only is solid, stretched over `rect`
+    fn corner_mask(x: f64, y: f64, side: f64) -> Collider {
+        Collider::Mask {
+            mask: Rc::new(AlphaMask { width: 2, height: 2, solid: vec![true, false, false, false] }),
+            rect: Rectangle { x, y, w: side, h: side },
+        }
+    }
+
+    // Overlapping is symmetric, so both orders are tested
+    fn assert_overlap(first: &Collider, second: &Collider, expected: bool) {
+        assert_eq!(first.overlaps(second), expected, "{:?} and {:?}", first, second);
+        assert_eq!(second.overlaps(first), expected, "{:?} and {:?}", second, first);
+    }
+
+    #[test]
+    fn rect_pairs() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+
+        assert_overlap(&square, &rect(5.0, 5.0, 10.0, 10.0), true);
+        assert_overlap(&square, &rect(10.0, 0.0, 10.0, 10.0), true);
+        assert_overlap(&square, &rect(11.0, 0.0, 10.0, 10.0), false);
+
+        assert_overlap(&square, &circle(5.0, 5.0, 1.0), true);
+        assert_overlap(&square, &circle(15.0, 5.0, 5.0), true);
+        // Near the corner, but out of reach of the round edge
+        assert_overlap(&square, &circle(13.0, 13.0, 4.0), false);
+
+        assert_overlap(&square, &capsule((-5.0, 5.0), (15.0, 5.0), 0.5), true);
+        assert_overlap(&square, &capsule((12.0, -5.0), (12.0, 15.0), 2.0), true);
+        assert_overlap(&square, &capsule((13.0, -5.0), (13.0, 15.0), 2.0), false);
+
+        assert_overlap(&square, &polygon(&[(5.0, 5.0), (20.0, 5.0), (20.0, 20.0)]), true);
+        assert_overlap(&square, &polygon(&[(10.0, 0.0), (20.0, 0.0), (20.0, 10.0)]), true);
+        assert_overlap(&square, &polygon(&[(12.0, 0.0), (20.0, 0.0), (20.0, 10.0)]), false);
+
+        assert_overlap(&square, &corner_mask(5.0, 5.0, 20.0), true);
+        assert_overlap(&square, &corner_mask(-15.0, 5.0, 20.0), false);
+    }
+
+    #[test]
+    fn circle_pairs() {
+        let round = circle(0.0, 0.0, 2.0);
+
+        assert_overlap(&round, &circle(3.0, 0.0, 2.0), true);
+        assert_overlap(&round, &circle(3.0, 0.0, 1.0), true);
+        assert_overlap(&round, &circle(3.0, 0.0, 0.5), false);
+
+        assert_overlap(&round, &capsule((-5.0, 3.0), (5.0, 3.0), 1.0), true);
+        assert_overlap(&round, &capsule((-5.0, 3.0), (5.0, 3.0), 0.5), false);
+
+        assert_overlap(&round, &polygon(&[(2.0, -5.0), (5.0, -5.0), (5.0, 5.0), (2.0, 5.0)]), true);
+        assert_overlap(&round, &polygon(&[(2.5, -5.0), (5.0, -5.0), (5.0, 5.0)]), false);
+
+        // Masks are tested at the center of their pixels, and only the
+        // top-left one is solid
+        assert_overlap(&round, &corner_mask(-1.0, -1.0, 4.0), true);
+        assert_overlap(&round, &corner_mask(1.5, -1.0, 4.0), false);
+    }
+
+    #[test]
+    fn capsule_pairs() {
+        let bar = capsule((0.0, 0.0), (10.0, 0.0), 1.0);
+
+        assert_overlap(&bar, &capsule((5.0, -5.0), (5.0, 5.0), 0.0), true);
+        assert_overlap(&bar, &capsule((0.0, 3.0), (10.0, 3.0), 2.0), true);
+        assert_overlap(&bar, &capsule((0.0, 3.0), (10.0, 3.0), 1.5), false);
+
+        assert_overlap(&bar, &polygon(&[(12.0, -1.0), (14.0, 0.0), (12.0, 1.0)]), false);
+        assert_overlap(&bar, &polygon(&[(11.0, -1.0), (14.0, 0.0), (11.0, 1.0)]), true);
+
+        assert_overlap(&bar, &corner_mask(8.0, -1.0, 4.0), true);
+        assert_overlap(&bar, &corner_mask(8.0, -4.0, 4.0), false);
+    }
+
+    #[test]
+    fn polygon_pairs() {
+        let triangle = polygon(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]);
+
+        assert_overlap(&triangle, &polygon(&[(2.0, 2.0), (3.0, 2.0), (2.0, 3.0)]), true);
+        // Sharing the hypotenuse
+        assert_overlap(&triangle, &polygon(&[(10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]), true);
+        assert_overlap(&triangle, &polygon(&[(6.0, 6.0), (10.0, 6.0), (6.0, 10.0)]), false);
+
+        // The points may go either way around
+        let reversed = polygon(&[(0.0, 10.0), (10.0, 0.0), (0.0, 0.0)]);
+        assert_overlap(&reversed, &polygon(&[(2.0, 2.0), (2.0, 3.0), (3.0, 2.0)]), true);
+
+        assert_overlap(&triangle, &corner_mask(0.0, 0.0, 4.0), true);
+        assert_overlap(&triangle, &corner_mask(6.0, 6.0, 4.0), false);
+    }
+
+    #[test]
+    fn mask_pairs() {
+        let mask = corner_mask(0.0, 0.0, 10.0);
+
+        assert_overlap(&mask, &corner_mask(2.0, 2.0, 10.0), true);
+        // Only the empty parts of both masks overlap
+        assert_overlap(&mask, &corner_mask(-5.0, 5.0, 10.0), false);
+
+        assert!(mask.contains_point(Vec2::new(2.0, 2.0)));
+        assert!(!mask.contains_point(Vec2::new(7.0, 7.0)));
+        assert!(!mask.contains_point(Vec2::new(20.0, 2.0)));
+    }
+
+    #[test]
+    fn degenerate_shapes() {
+        let square = rect(0.0, 0.0, 10.0, 10.0);
+
+        // Without any point, a polygon collides with nothing
+        let empty = polygon(&[]);
+        assert_eq!(empty.bounds(), Rectangle::with_size(0.0, 0.0));
+        assert_overlap(&empty, &square, false);
+        assert_overlap(&empty, &circle(0.0, 0.0, 5.0), false);
+        assert_overlap(&empty, &empty, false);
+        assert!(!empty.contains_point(Vec2::zero()));
+
+        // A single point, and a segment
+        assert_overlap(&polygon(&[(5.0, 5.0)]), &square, true);
+        assert_overlap(&polygon(&[(15.0, 5.0)]), &square, false);
+        assert_overlap(&polygon(&[(-5.0, 5.0), (15.0, 5.0)]), &square, true);
+        assert_overlap(&polygon(&[(-5.0, 15.0), (15.0, 15.0)]), &square, false);
+        assert_overlap(&polygon(&[(0.0, 0.0), (4.0, 0.0)]), &circle(2.0, 1.0, 1.0), true);
+
+        // A circle without radius is a point, and an empty rectangle too
+        assert_overlap(&circle(5.0, 5.0, 0.0), &square, true);
+        assert_overlap(&circle(10.0, 5.0, 0.0), &square, true);
+        assert_overlap(&rect(5.0, 5.0, 0.0, 0.0), &square, true);
+        assert_overlap(&rect(5.0, 5.0, 0.0, 0.0), &circle(5.0, 6.0, 1.0), true);
+        assert_overlap(&rect(5.0, 5.0, 0.0, 0.0), &circle(5.0, 7.0, 1.0), false);
+
+        // Near a shape without area, but further than the radius, while the
+        // bounds overlap
+        assert_overlap(&rect(5.0, 5.0, 0.0, 0.0), &circle(5.9, 5.9, 1.0), false);
+        assert_overlap(&rect(5.0, 5.0, 0.0, 10.0), &circle(5.8, 15.8, 1.0), false);
+        assert_overlap(&polygon(&[(5.0, 5.0), (5.0, 5.0), (5.0, 5.0)]), &circle(5.9, 5.9, 1.0), false);
+
+        // A point on the line of a shape without area lies inside only
+        // between its ends
+        let segment = polygon(&[(0.0, 0.0), (10.0, 0.0), (4.0, 0.0)]);
+        assert!(segment.contains_point(Vec2::new(7.0, 0.0)));
+        assert!(!segment.contains_point(Vec2::new(12.0, 0.0)));
+        assert!(!polygon(&[(5.0, 5.0), (5.0, 5.0), (5.0, 5.0)]).contains_point(Vec2::new(6.0, 6.0)));
+    }
+}
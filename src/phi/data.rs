@@ -1,4 +1,170 @@
 use ::sdl2::rect::Rect as SdlRect;
+use ::std::collections::HashMap;
+use ::std::iter::Enumerate;
+use ::std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+use ::std::slice;
+
+// A position, a direction or a velocity in two dimensions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2 {
+    pub fn new(x: f64, y: f64) -> Vec2 {
+        Vec2 { x, y }
+    }
+
+    pub fn zero() -> Vec2 {
+        Vec2::new(0.0, 0.0)
+    }
+
+    // A vector of length `length` pointing at `angle` radians, clockwise from
+    // the right since the y axis points down.
+    pub fn from_angle(angle: f64, length: f64) -> Vec2 {
+        Vec2::new(angle.cos() * length, angle.sin() * length)
+    }
+
+    pub fn length(self) -> f64 {
+        self.length_squared().sqrt()
+    }
+
+    // Cheaper than `length`, for comparing distances.
+    pub fn length_squared(self) -> f64 {
+        self.dot(self)
+    }
+
+    // The vector pointing in the same direction with a length of 1, or the
+    // zero vector which has no direction.
+    pub fn normalize(self) -> Vec2 {
+        let length = self.length();
+        if length == 0.0 { self } else { self / length }
+    }
+
+    // Shortens the vector to `max` if it is longer.
+    pub fn clamp_length(self, max: f64) -> Vec2 {
+        if self.length_squared() > max * max {
+            self.normalize() * max
+        } else {
+            self
+        }
+    }
+
+    pub fn dot(self, other: Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // The z coordinate of the cross product, which is positive when `other`
+    // lies clockwise from `self`.
+    pub fn cross(self, other: Vec2) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn distance(self, other: Vec2) -> f64 {
+        (other - self).length()
+    }
+
+    // The angle of the vector, in radians, clockwise from the right.
+    pub fn angle(self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    // Rotates the vector by `angle` radians, clockwise.
+    pub fn rotate(self, angle: f64) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    // The vector rotated by a quarter turn, clockwise.
+    pub fn perpendicular(self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
+
+    // Interpolates between `self`, at `t = 0`, and `other`, at `t = 1`.
+    pub fn lerp(self, other: Vec2, t: f64) -> Vec2 {
+        self + (other - self) * t
+    }
+}
+
+impl From<(f64, f64)> for Vec2 {
+    fn from(pair: (f64, f64)) -> Vec2 {
+        Vec2::new(pair.0, pair.1)
+    }
+}
+
+impl From<Vec2> for (f64, f64) {
+    fn from(vec: Vec2) -> (f64, f64) {
+        (vec.x, vec.y)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, factor: f64) -> Vec2 {
+        Vec2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Mul<Vec2> for f64 {
+    type Output = Vec2;
+
+    fn mul(self, vec: Vec2) -> Vec2 {
+        vec * self
+    }
+}
+
+impl Div<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, divisor: f64) -> Vec2 {
+        Vec2::new(self.x / divisor, self.y / divisor)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f64> for Vec2 {
+    fn mul_assign(&mut self, factor: f64) {
+        *self = *self * factor;
+    }
+}
+
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Rectangle {
@@ -10,11 +176,37 @@ pub struct Rectangle {
 
 impl Rectangle {
     // Convert our Real defined rect to int SdlRect
+    // None if the rectangle is empty, has a negative size or is too large
     pub fn to_sdl(self) -> Option<SdlRect> {
-        assert!(self.w >= 0.0 && self.h >= 0.0);
+        if self.w < 0.0 || self.h < 0.0 {
+            return None;
+        }
+
         // SldRect::new : (i32, i32, u32, u32) -> Result<Option<SdlRect>>
         SdlRect::new(self.x as i32, self.y as i32, self.w as u32, self.h as u32)
-            .unwrap()
+            .unwrap_or(None)
+    }
+
+    // Convert to an SdlRect covering the part of the rectangle which lies
+    // inside of `clip`, such as the screen. Unlike `to_sdl`, a negative size
+    // is flipped rather than rejected, and the edges are rounded to the
+    // nearest pixel so that neighbouring rectangles do not leave gaps.
+    // None if nothing is left to draw
+    pub fn to_sdl_clipped(self, clip: Rectangle) -> Option<SdlRect> {
+        let rect = self.normalize().intersection(clip.normalize())?;
+
+        let left = rect.left().round();
+        let top = rect.top().round();
+        let right = rect.right().round();
+        let bottom = rect.bottom().round();
+
+        if left < i32::MIN as f64 || top < i32::MIN as f64 ||
+           right > i32::MAX as f64 || bottom > i32::MAX as f64 {
+            return None;
+        }
+
+        SdlRect::new(left as i32, top as i32, (right - left) as u32, (bottom - top) as u32)
+            .unwrap_or(None)
     }
 
     // Return a maybe moved Rectangle
@@ -51,6 +243,12 @@ impl Rectangle {
         ymax >= self.y && ymax <= self.y + self.h
     }
 
+    /// Whether `point` lies inside of the rectangle.
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        point.x >= self.x && point.x < self.x + self.w &&
+        point.y >= self.y && point.y < self.y + self.h
+    }
+
     pub fn overlaps(&self, other: Rectangle) -> bool {
         self.x < other.x + other.w &&
         self.x + self.w > other.x &&
@@ -67,35 +265,661 @@ impl Rectangle {
         }
     }
 
-    pub fn center_at(self, center: (f64, f64)) -> Rectangle {
+    pub fn center_at(self, center: Vec2) -> Rectangle {
         Rectangle{
-            x: center.0 - self.w / 2.0,
-            y: center.1 - self.h / 2.0,
+            x: center.x - self.w / 2.0,
+            y: center.y - self.h / 2.0,
             ..self
         }
     }
 
-    pub fn center(self) -> (f64, f64) {
+    pub fn center(self) -> Vec2 {
         let x = self.x + self.w / 2.0;
         let y = self.y + self.h / 2.0;
-        (x,y)
+        Vec2::new(x, y)
+    }
+
+    // Same size, moved by `offset`
+    pub fn translate(self, offset: Vec2) -> Rectangle {
+        Rectangle {
+            x: self.x + offset.x,
+            y: self.y + offset.y,
+            ..self
+        }
+    }
+
+    pub fn left(self) -> f64 {
+        self.x
+    }
+
+    pub fn right(self) -> f64 {
+        self.x + self.w
+    }
+
+    pub fn top(self) -> f64 {
+        self.y
+    }
+
+    pub fn bottom(self) -> f64 {
+        self.y + self.h
+    }
+
+    // The corners, clockwise from the top-left one
+    pub fn corners(self) -> [Vec2; 4] {
+        [
+            Vec2::new(self.left(), self.top()),
+            Vec2::new(self.right(), self.top()),
+            Vec2::new(self.right(), self.bottom()),
+            Vec2::new(self.left(), self.bottom()),
+        ]
+    }
+
+    // Same area, with the corners swapped so that the size is positive
+    pub fn normalize(self) -> Rectangle {
+        Rectangle {
+            x: self.x.min(self.x + self.w),
+            y: self.y.min(self.y + self.h),
+            w: self.w.abs(),
+            h: self.h.abs(),
+        }
+    }
+
+    // The area shared by both rectangles
+    // None if they do not overlap
+    pub fn intersection(self, other: Rectangle) -> Option<Rectangle> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if left < right && top < bottom {
+            Some(Rectangle { x: left, y: top, w: right - left, h: bottom - top })
+        } else {
+            None
+        }
+    }
+
+    // The smallest rectangle which contains both rectangles
+    pub fn union(self, other: Rectangle) -> Rectangle {
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        Rectangle { x: left, y: top, w: right - left, h: bottom - top }
+    }
+
+    // Grows the rectangle by `dx` on the left and on the right, and by `dy`
+    // on the top and on the bottom, keeping its center
+    pub fn inflate(self, dx: f64, dy: f64) -> Rectangle {
+        Rectangle {
+            x: self.x - dx,
+            y: self.y - dy,
+            w: self.w + dx * 2.0,
+            h: self.h + dy * 2.0,
+        }
+    }
+
+    // Shrinks the rectangle by `dx` on the left and on the right, and by `dy`
+    // on the top and on the bottom, down to an empty rectangle at its center
+    pub fn deflate(self, dx: f64, dy: f64) -> Rectangle {
+        let dx = dx.min(self.w / 2.0);
+        let dy = dy.min(self.h / 2.0);
+        self.inflate(-dx, -dy)
+    }
+
+    // Casts a ray from `origin` towards `direction`, and returns the factor
+    // of `direction` at which it enters the rectangle, that is 0 if `origin`
+    // already lies inside
+    // None if the ray misses the rectangle
+    #[cfg(test)]
+    pub fn ray_intersection(self, origin: Vec2, direction: Vec2) -> Option<f64> {
+        // Slab method: the ray is inside of the rectangle while it lies both
+        // between its left and right edges and between its top and bottom ones
+        let slab = |origin: f64, direction: f64, min: f64, max: f64| {
+            if direction == 0.0 {
+                if origin >= min && origin <= max {
+                    Some((f64::NEG_INFINITY, f64::INFINITY))
+                } else {
+                    None
+                }
+            } else {
+                let (t1, t2) = ((min - origin) / direction, (max - origin) / direction);
+                Some((t1.min(t2), t1.max(t2)))
+            }
+        };
+
+        let (x_enter, x_exit) = slab(origin.x, direction.x, self.left(), self.right())?;
+        let (y_enter, y_exit) = slab(origin.y, direction.y, self.top(), self.bottom())?;
+
+        let enter = x_enter.max(y_enter).max(0.0);
+        let exit = x_exit.min(y_exit);
+
+        if enter <= exit {
+            Some(enter)
+        } else {
+            None
+        }
+    }
+
+    // The first point of the segment from `a` to `b` which lies inside of
+    // the rectangle
+    // None if the whole segment lies outside
+    #[cfg(test)]
+    pub fn segment_intersection(self, a: Vec2, b: Vec2) -> Option<Vec2> {
+        match self.ray_intersection(a, b - a) {
+            Some(t) if t <= 1.0 => Some(a.lerp(b, t)),
+            _ => None,
+        }
     }
 }
 
-pub struct MaybeAlive<T> {
-    pub alive: bool,
-    pub value: T,
+// Broad phase of the collision detection: splits the plane into square cells,
+// and remembers which cells every value covers. Only the values which share a
+// cell with a rectangle may overlap it, which avoids testing every pair.
+//
+// The grid is meant to be cleared and filled again every frame.
+pub struct Grid<T> {
+    cell_size: f64,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    values: Vec<(Rectangle, T)>,
 }
 
-impl<T> MaybeAlive<T> {
-    // Check if value is alive
-    // If so, return Some(value) else None
-    pub fn as_option(self) -> Option<T> {
-        if self.alive {
-            Some(self.value)
+impl<T> Grid<T> {
+    // `cell_size` should be about the size of the largest values, so that
+    // each one covers few cells
+    pub fn new(cell_size: f64) -> Grid<T> {
+        Grid {
+            cell_size,
+            cells: HashMap::new(),
+            values: Vec::new(),
         }
-        else {
-            None
+    }
+
+    pub fn clear(&mut self) {
+        // Keep the allocations of the cells for the next frame
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.values.clear();
+    }
+
+    pub fn insert(&mut self, bounds: Rectangle, value: T) {
+        let index = self.values.len();
+        self.values.push((bounds, value));
+
+        let (first_x, first_y, last_x, last_y) = self.cell_range(bounds);
+        for x in first_x..last_x + 1 {
+            for y in first_y..last_y + 1 {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
         }
     }
+
+    // Calls `visit` with every value whose bounds overlap `rect`, once each
+    pub fn query<F>(&self, rect: Rectangle, mut visit: F) where F: FnMut(&T) {
+        let (first_x, first_y, last_x, last_y) = self.cell_range(rect);
+
+        for x in first_x..last_x + 1 {
+            for y in first_y..last_y + 1 {
+                let indices = match self.cells.get(&(x, y)) {
+                    Some(indices) => indices,
+                    None => continue,
+                };
+
+                for &index in indices {
+                    let (bounds, ref value) = self.values[index];
+
+                    // A value which covers several of the cells is only
+                    // visited in the first one which it shares with `rect`
+                    let (value_x, value_y, _, _) = self.cell_range(bounds);
+                    if (x, y) == (value_x.max(first_x), value_y.max(first_y)) && bounds.overlaps(rect) {
+                        visit(value);
+                    }
+                }
+            }
+        }
+    }
+
+    // The first and last columns and rows of cells covered by `rect`, in the
+    // order first_x, first_y, last_x, last_y
+    fn cell_range(&self, rect: Rectangle) -> (i32, i32, i32, i32) {
+        ((rect.x / self.cell_size).floor() as i32,
+         (rect.y / self.cell_size).floor() as i32,
+         ((rect.x + rect.w) / self.cell_size).floor() as i32,
+         ((rect.y + rect.h) / self.cell_size).floor() as i32)
+    }
+}
+
+// Refers to a value stored in a Pool. A handle stays valid for as long as its
+// value is in the pool, and never refers to another value once it has been
+// removed, even if the slot is reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: usize,
+    generation: u32,
+}
+
+struct Slot<T> {
+    // Incremented every time the slot is emptied
+    generation: u32,
+    value: Option<T>,
+}
+
+// Stores the entities of a game, such as bullets or asteroids. Values are
+// updated in place rather than rebuilt every frame, and the slots of removed
+// values are reused, so that a pool stops allocating once it has reached its
+// largest size. Values may still move in memory when the pool grows, so they
+// should be referred to by their handle rather than by address.
+pub struct Pool<T> {
+    slots: Vec<Slot<T>>,
+    // Indices of the empty slots
+    free: Vec<usize>,
+    // Values to remove on the next call to `sweep`
+    dead: Vec<Handle>,
+    len: usize,
+}
+
+impl<T> Pool<T> {
+    pub fn new() -> Pool<T> {
+        Pool {
+            slots: Vec::new(),
+            free: Vec::new(),
+            dead: Vec::new(),
+            len: 0,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> Handle {
+        self.len += 1;
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index];
+            slot.value = Some(value);
+            return Handle { index, generation: slot.generation };
+        }
+
+        self.slots.push(Slot { generation: 0, value: Some(value) });
+        Handle { index: self.slots.len() - 1, generation: 0 }
+    }
+
+    // None if the value has been removed
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        match self.slots.get(handle.index) {
+            Some(slot) if slot.generation == handle.generation => slot.value.as_ref(),
+            _ => None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        match self.slots.get_mut(handle.index) {
+            Some(slot) if slot.generation == handle.generation => slot.value.as_mut(),
+            _ => None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    // Removes the value right away
+    // None if it had already been removed
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let value = match self.slots.get_mut(handle.index) {
+            Some(slot) if slot.generation == handle.generation => slot.value.take(),
+            _ => None,
+        };
+
+        if value.is_some() {
+            self.slots[handle.index].generation = self.slots[handle.index].generation.wrapping_add(1);
+            self.free.push(handle.index);
+            self.len -= 1;
+        }
+        value
+    }
+
+    // Marks the value to be removed on the next call to `sweep`. Until then,
+    // it can still be reached, so that for instance a bullet which hits two
+    // asteroids during the same frame destroys both of them.
+    pub fn kill(&mut self, handle: Handle) {
+        self.dead.push(handle);
+    }
+
+    // Removes the values marked by `kill`
+    pub fn sweep(&mut self) {
+        let dead = ::std::mem::take(&mut self.dead);
+        for &handle in &dead {
+            self.remove(handle);
+        }
+
+        // Keep the allocation for the next frame
+        self.dead = dead;
+        self.dead.clear();
+    }
+
+    // Updates every value in place, and removes those for which `update`
+    // returns false
+    pub fn retain<F: FnMut(&mut T) -> bool>(&mut self, mut update: F) {
+        for index in 0..self.slots.len() {
+            let alive = match self.slots[index].value {
+                Some(ref mut value) => update(value),
+                None => continue,
+            };
+
+            if !alive {
+                let handle = Handle { index, generation: self.slots[index].generation };
+                self.remove(handle);
+            }
+        }
+    }
+
+    // The values and their handles, in the order of their slots
+    pub fn iter(&self) -> PoolIter<'_, T> {
+        PoolIter { slots: self.slots.iter().enumerate() }
+    }
+
+    #[cfg(test)]
+    pub fn iter_mut(&mut self) -> PoolIterMut<'_, T> {
+        PoolIterMut { slots: self.slots.iter_mut().enumerate() }
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Pool<T> {
+        Pool::new()
+    }
+}
+
+impl<T> Extend<T> for Pool<T> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+pub struct PoolIter<'a, T: 'a> {
+    slots: Enumerate<slice::Iter<'a, Slot<T>>>,
+}
+
+impl<'a, T> Iterator for PoolIter<'a, T> {
+    type Item = (Handle, &'a T);
+
+    fn next(&mut self) -> Option<(Handle, &'a T)> {
+        for (index, slot) in &mut self.slots {
+            if let Some(ref value) = slot.value {
+                return Some((Handle { index, generation: slot.generation }, value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+pub struct PoolIterMut<'a, T: 'a> {
+    slots: Enumerate<slice::IterMut<'a, Slot<T>>>,
+}
+
+#[cfg(test)]
+impl<'a, T> Iterator for PoolIterMut<'a, T> {
+    type Item = (Handle, &'a mut T);
+
+    fn next(&mut self) -> Option<(Handle, &'a mut T)> {
+        for (index, slot) in &mut self.slots {
+            let generation = slot.generation;
+            if let Some(ref mut value) = slot.value {
+                return Some((Handle { index, generation }, value));
+            }
+        }
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn vec2_arithmetic() {
+        let a = Vec2::new(1.0, 2.0);
+        let b = Vec2::new(3.0, -4.0);
+
+        assert_eq!(a + b, Vec2::new(4.0, -2.0));
+        assert_eq!(a - b, Vec2::new(-2.0, 6.0));
+        assert_eq!(a * 2.0, Vec2::new(2.0, 4.0));
+        assert_eq!(2.0 * a, Vec2::new(2.0, 4.0));
+        assert_eq!(b / 2.0, Vec2::new(1.5, -2.0));
+        assert_eq!(-a, Vec2::new(-1.0, -2.0));
+        assert_eq!(a.dot(b), -5.0);
+        assert_eq!(a.cross(b), -10.0);
+
+        let mut c = a;
+        c += b;
+        assert_eq!(c, a + b);
+        c -= b;
+        assert_eq!(c, a);
+        c *= 3.0;
+        assert_eq!(c, Vec2::new(3.0, 6.0));
+
+        assert_eq!(Vec2::from((1.0, 2.0)), a);
+        assert_eq!(<(f64, f64)>::from(a), (1.0, 2.0));
+        assert_eq!(a.lerp(b, 0.5), Vec2::new(2.0, -1.0));
+    }
+
+    #[test]
+    fn vec2_length() {
+        let v = Vec2::new(3.0, 4.0);
+        assert_eq!(v.length(), 5.0);
+        assert_eq!(v.length_squared(), 25.0);
+        assert_eq!(Vec2::zero().distance(v), 5.0);
+        assert_close(v.normalize(), Vec2::new(0.6, 0.8));
+        assert_close(v.clamp_length(2.5), Vec2::new(1.5, 2.0));
+        assert_eq!(v.clamp_length(10.0), v);
+
+        // The zero vector has no direction, and is left as it is
+        assert_eq!(Vec2::zero().normalize(), Vec2::zero());
+        assert_eq!(Vec2::zero().clamp_length(1.0), Vec2::zero());
+    }
+
+    #[test]
+    fn vec2_angle() {
+        use ::std::f64::consts::PI;
+
+        // Angles go clockwise on screen, since the y axis points down
+        assert_close(Vec2::from_angle(0.0, 2.0), Vec2::new(2.0, 0.0));
+        assert_close(Vec2::from_angle(PI / 2.0, 2.0), Vec2::new(0.0, 2.0));
+        assert_close(Vec2::from_angle(PI, 1.0), Vec2::new(-1.0, 0.0));
+
+        assert_eq!(Vec2::new(0.0, 1.0).angle(), PI / 2.0);
+        assert_eq!(Vec2::new(-1.0, 0.0).angle(), PI);
+        assert!((Vec2::from_angle(1.2, 3.0).angle() - 1.2).abs() < 1e-9);
+
+        assert_close(Vec2::new(1.0, 0.0).rotate(PI / 2.0), Vec2::new(0.0, 1.0));
+        assert_eq!(Vec2::new(1.0, 0.0).perpendicular(), Vec2::new(0.0, 1.0));
+        assert!(Vec2::new(1.0, 0.0).cross(Vec2::new(0.0, 1.0)) > 0.0);
+    }
+
+    // The values found by a query, sorted as the order of a query is not
+    // specified
+    fn query_all<T: Clone + Ord>(grid: &Grid<T>, rect: Rectangle) -> Vec<T> {
+        let mut found = Vec::new();
+        grid.query(rect, |value| found.push(value.clone()));
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn grid_finds_values_at_negative_coordinates() {
+        let mut grid = Grid::new(32.0);
+        grid.insert(Rectangle { x: -50.0, y: -50.0, w: 20.0, h: 20.0 }, 'a');
+        grid.insert(Rectangle { x: 10.0, y: -40.0, w: 20.0, h: 20.0 }, 'b');
+
+        assert_eq!(query_all(&grid, Rectangle { x: -45.0, y: -45.0, w: 1.0, h: 1.0 }), vec!['a']);
+        assert_eq!(query_all(&grid, Rectangle { x: 15.0, y: -35.0, w: 1.0, h: 1.0 }), vec!['b']);
+
+        // Same cell as `a`, but outside of its bounds
+        assert!(query_all(&grid, Rectangle { x: -63.0, y: -63.0, w: 1.0, h: 1.0 }).is_empty());
+    }
+
+    #[test]
+    fn grid_reports_each_value_once() {
+        let mut grid = Grid::new(10.0);
+        // Covers nine cells
+        grid.insert(Rectangle { x: -5.0, y: -5.0, w: 20.0, h: 20.0 }, 1);
+        grid.insert(Rectangle { x: 40.0, y: 40.0, w: 5.0, h: 5.0 }, 2);
+
+        let everything = Rectangle { x: -100.0, y: -100.0, w: 200.0, h: 200.0 };
+        assert_eq!(query_all(&grid, everything), vec![1, 2]);
+
+        // Starting inside of the cells of the first value
+        assert_eq!(query_all(&grid, Rectangle { x: 5.0, y: 5.0, w: 50.0, h: 50.0 }), vec![1, 2]);
+
+        grid.clear();
+        assert!(query_all(&grid, everything).is_empty());
+    }
+
+    #[test]
+    fn rectangle_intersection_and_union() {
+        let a = Rectangle { x: 0.0, y: 0.0, w: 10.0, h: 10.0 };
+        let b = Rectangle { x: 5.0, y: -5.0, w: 10.0, h: 10.0 };
+
+        assert_eq!(a.intersection(b), Some(Rectangle { x: 5.0, y: 0.0, w: 5.0, h: 5.0 }));
+        assert_eq!(a.union(b), Rectangle { x: 0.0, y: -5.0, w: 15.0, h: 15.0 });
+
+        // Touching edges share no area
+        let c = Rectangle { x: 10.0, y: 0.0, w: 10.0, h: 10.0 };
+        assert_eq!(a.intersection(c), None);
+        assert_eq!(a.union(c), Rectangle { x: 0.0, y: 0.0, w: 20.0, h: 10.0 });
+    }
+
+    #[test]
+    fn rectangle_deflate() {
+        let rect = Rectangle { x: 0.0, y: 0.0, w: 10.0, h: 20.0 };
+
+        assert_eq!(rect.deflate(2.0, 3.0), Rectangle { x: 2.0, y: 3.0, w: 6.0, h: 14.0 });
+        assert_eq!(rect.deflate(2.0, 3.0).inflate(2.0, 3.0), rect);
+
+        // Deflating too much leaves an empty rectangle at the center
+        assert_eq!(rect.deflate(8.0, 30.0), Rectangle { x: 5.0, y: 10.0, w: 0.0, h: 0.0 });
+    }
+
+    #[test]
+    fn rectangle_ray_intersection() {
+        let rect = Rectangle { x: 10.0, y: 10.0, w: 10.0, h: 10.0 };
+
+        assert_eq!(rect.ray_intersection(Vec2::new(0.0, 15.0), Vec2::new(2.0, 0.0)), Some(5.0));
+        assert_eq!(rect.ray_intersection(Vec2::new(0.0, 15.0), Vec2::new(-2.0, 0.0)), None);
+        assert_eq!(rect.ray_intersection(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)), Some(10.0));
+
+        // A ray which starts inside enters right away
+        assert_eq!(rect.ray_intersection(Vec2::new(15.0, 15.0), Vec2::new(1.0, 0.0)), Some(0.0));
+
+        // Without a direction, the ray is only the origin
+        assert_eq!(rect.ray_intersection(Vec2::new(15.0, 15.0), Vec2::zero()), Some(0.0));
+        assert_eq!(rect.ray_intersection(Vec2::new(0.0, 0.0), Vec2::zero()), None);
+
+        assert_eq!(rect.segment_intersection(Vec2::new(0.0, 15.0), Vec2::new(20.0, 15.0)),
+                   Some(Vec2::new(10.0, 15.0)));
+        assert_eq!(rect.segment_intersection(Vec2::new(0.0, 15.0), Vec2::new(5.0, 15.0)), None);
+    }
+
+    #[test]
+    fn rectangle_to_sdl_clipped() {
+        let screen = Rectangle::with_size(100.0, 100.0);
+
+        // A negative size is flipped rather than rejected
+        let flipped = Rectangle { x: 30.0, y: 40.0, w: -20.0, h: -10.0 };
+        assert_eq!(flipped.to_sdl(), None);
+        assert_eq!(flipped.to_sdl_clipped(screen), SdlRect::new(10, 30, 20, 10).unwrap());
+
+        // Only the part on screen is kept
+        let partly_off = Rectangle { x: -10.0, y: 90.0, w: 30.0, h: 30.0 };
+        assert_eq!(partly_off.to_sdl_clipped(screen), SdlRect::new(0, 90, 20, 10).unwrap());
+
+        let off = Rectangle { x: 100.0, y: 0.0, w: 10.0, h: 10.0 };
+        assert_eq!(off.to_sdl_clipped(screen), None);
+    }
+
+    #[test]
+    fn pool_handles_go_stale_when_the_slot_is_reused() {
+        let mut pool = Pool::new();
+        let first = pool.insert("first");
+        assert_eq!(pool.remove(first), Some("first"));
+
+        let second = pool.insert("second");
+        assert_eq!(pool.get(first), None);
+        assert!(!pool.contains(first));
+        assert_eq!(pool.get_mut(first), None);
+        assert_eq!(pool.remove(first), None);
+        assert_eq!(pool.get(second), Some(&"second"));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn pool_kill_waits_for_sweep() {
+        let mut pool = Pool::new();
+        let a = pool.insert(1);
+        let b = pool.insert(2);
+
+        pool.kill(a);
+        assert_eq!(pool.get(a), Some(&1));
+        assert_eq!(pool.len(), 2);
+
+        pool.sweep();
+        assert_eq!(pool.get(a), None);
+        assert_eq!(pool.get(b), Some(&2));
+        assert_eq!(pool.iter().collect::<Vec<_>>(), vec![(b, &2)]);
+    }
+
+    #[test]
+    fn pool_double_kill_removes_once() {
+        let mut pool = Pool::new();
+        let a = pool.insert(1);
+        pool.kill(a);
+        pool.kill(a);
+        pool.sweep();
+        assert!(pool.is_empty());
+
+        // A stale kill must not remove the value which took the slot over
+        let b = pool.insert(2);
+        pool.kill(a);
+        pool.sweep();
+        assert_eq!(pool.get(b), Some(&2));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn pool_updates_in_place() {
+        let mut pool = Pool::new();
+        pool.extend(vec![1, 2, 3, 4]);
+
+        for (_, value) in pool.iter_mut() {
+            *value *= 10;
+        }
+        pool.retain(|value| {
+            *value += 1;
+            *value != 21
+        });
+
+        let values: Vec<i32> = pool.iter().map(|(_, &value)| value).collect();
+        assert_eq!(values, vec![11, 31, 41]);
+        assert_eq!(pool.len(), 3);
+    }
 }
@@ -0,0 +1,62 @@
+use ::phi::data::Rectangle;
+use ::sdl2::ErrorMessage;
+use ::std::error::Error;
+use ::std::fmt;
+
+/// The errors which may happen while setting up the game or loading its
+/// assets.
+#[derive(Debug)]
+pub enum PhiError {
+    /// SDL could not perform an operation, such as creating the window.
+    Sdl(String),
+
+    /// The image at `path` could not be loaded.
+    Asset { path: String, reason: String },
+
+    /// The font at `path` could not be loaded, or could not render some text.
+    Font { path: String, reason: String },
+
+    /// A region does not fit inside of the sprite it is taken from.
+    InvalidRegion(Rectangle),
+
+    /// The configuration file at `path` could not be read or written.
+    Config { path: String, reason: String },
+
+    /// The replay at `path` could not be read or written.
+    Replay { path: String, reason: String },
+
+    /// An animation was given a frame rate of zero.
+    InvalidFps,
+}
+
+/// The result of the operations of Phi which may fail.
+pub type PhiResult<T> = Result<T, PhiError>;
+
+impl fmt::Display for PhiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PhiError::Sdl(ref reason) =>
+                write!(f, "SDL error: {}", reason),
+            PhiError::Asset { ref path, ref reason } =>
+                write!(f, "Could not load the image '{}': {}", path, reason),
+            PhiError::Font { ref path, ref reason } =>
+                write!(f, "Could not use the font '{}': {}", path, reason),
+            PhiError::InvalidRegion(rect) =>
+                write!(f, "The region {:?} lies outside of its sprite", rect),
+            PhiError::Config { ref path, ref reason } =>
+                write!(f, "Invalid configuration file '{}': {}", path, reason),
+            PhiError::Replay { ref path, ref reason } =>
+                write!(f, "Invalid replay '{}': {}", path, reason),
+            PhiError::InvalidFps =>
+                write!(f, "An animation cannot run at 0 frames per second"),
+        }
+    }
+}
+
+impl Error for PhiError {}
+
+impl From<ErrorMessage> for PhiError {
+    fn from(error: ErrorMessage) -> PhiError {
+        PhiError::Sdl(error.0)
+    }
+}
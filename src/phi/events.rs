@@ -1,20 +1,56 @@
 macro_rules! struct_events {
     (
-        keyboard: { $( $k_alias:ident : $k_sdl:ident),* },
-
         // Match against a pattern
         else: { $( $e_alias:ident : $e_sdl:pat),* }
     )
     => {
+        use ::phi::input::{Action, Bindings, Edit, Input, InputEvent, InputSource, Sign};
+        use ::phi::replay::Frame;
         use ::sdl2::EventPump;
+        use ::sdl2::GameControllerSubsystem;
+        use ::sdl2::controller::{Axis, Button, GameController};
+        use ::sdl2::keyboard::TextInputUtil;
+        use ::sdl2::mouse::Mouse;
+        use ::std::collections::HashMap;
+
+        /// How far a stick must be pushed before an action bound to it counts
+        /// as pressed.
+        const AXIS_PRESS_THRESHOLD: f64 = 0.5;
 
         pub struct ImmediateEvents{
-            resize: Option<(u32, u32)>,
-            // For every keyboard event -> Option<bool>
-            //  Some(True) = just pressed
-            //  Some(False) = just released
-            // None = nothing happening
-            $( pub $k_alias: Option<bool>, )*
+            /// The new size of the window, in pixels, if it was resized during
+            /// this frame. Views are also notified through `View::resize`.
+            pub resize: Option<(u32, u32)>,
+            /// Whether the window gained or lost the keyboard focus, or was
+            /// minimized or restored, during this frame.
+            pub focus_gained: bool,
+            pub focus_lost: bool,
+            pub minimized: bool,
+            pub restored: bool,
+
+            // Every input pressed during this frame, as many times as it
+            // happened. Keys held down by the operating system's auto-repeat
+            // are not reported again, and the halves of the axes are pressed
+            // when they cross `AXIS_PRESS_THRESHOLD`.
+            pressed: Vec<Input>,
+
+            /// Whether the mouse moved during this frame.
+            pub mouse_moved: bool,
+            /// How far the wheel was scrolled during this frame, horizontally
+            /// and vertically. Positive values scroll right and away from the
+            /// player.
+            pub wheel: (i32, i32),
+
+            /// The characters typed during this frame, while text input is
+            /// active.
+            pub text: String,
+            /// The characters typed and erased during this frame, in the order
+            /// in which it happened, counting the repetitions of a held
+            /// backspace, while text input is active.
+            pub edits: Vec<Edit>,
+            /// Whether the text was submitted, while text input is active.
+            pub submit: bool,
+
             $( pub $e_alias : bool ),*
         }
 
@@ -22,63 +58,483 @@ macro_rules! struct_events {
             pub fn new() -> ImmediateEvents {
                 ImmediateEvents {
                     resize: None,
-                    // Default everything None
-                    $( $k_alias: None, )*
+                    focus_gained: false,
+                    focus_lost: false,
+                    minimized: false,
+                    restored: false,
+                    pressed: Vec::new(),
+                    mouse_moved: false,
+                    wheel: (0, 0),
+                    text: String::new(),
+                    edits: Vec::new(),
+                    submit: false,
                     $( $e_alias: false),*
                 }
             }
+
+            /// Whether `key` has just been pressed.
+            pub fn key_pressed(&self, key: ::sdl2::keyboard::Keycode) -> bool {
+                self.input_pressed(Input::Key(key))
+            }
+
+            /// Whether the mouse `button` has just been pressed.
+            pub fn mouse_pressed(&self, button: Mouse) -> bool {
+                self.input_pressed(Input::Mouse(button))
+            }
+
+            /// Whether `input` has just been pressed, or pushed past the
+            /// threshold for an axis.
+            pub fn input_pressed(&self, input: Input) -> bool {
+                self.pressed.contains(&input)
+            }
+
+            /// How many times `input` was pressed during this frame. A quick
+            /// tap-release-tap counts as two presses.
+            pub fn press_count(&self, input: Input) -> usize {
+                self.pressed.iter().filter(|&&other| other == input).count()
+            }
         }
 
         pub struct Events {
             pump: EventPump,
             pub now: ImmediateEvents,
 
-            $( pub $k_alias: bool),*
+            /// The inputs which trigger every action. These may be changed at
+            /// any time, and are taken into account from the next query on.
+            pub bindings: Bindings,
+
+            /// The part of the range of the sticks, from 0 to 1, which is
+            /// ignored so that a resting stick does not move anything.
+            pub deadzone: f64,
+
+            // Every input currently held, with how long it has been held at
+            // the start of the current step, in seconds
+            held: HashMap<Input, f64>,
+            // The time simulated by the current step
+            step: f64,
+
+            // Controllers are opened as they are plugged in. The state of all
+            // of them is merged, so that any may be used to play.
+            controller_subsystem: Option<GameControllerSubsystem>,
+            controllers: Vec<GameController>,
+            // The controllers which hold every button. A button counts as
+            // held until all of them have released it.
+            buttons: HashMap<Button, Vec<i32>>,
+            // The raw position of every axis of every controller, from -1 to 1
+            axes: HashMap<(i32, Axis), f64>,
+
+            // The position of the cursor, in logical units
+            mouse_position: (f64, f64),
+
+            text_input: TextInputUtil,
+            // The text being composed by an input method, until it is
+            // confirmed or cancelled
+            composition: Option<String>,
+
+            // The inputs are appended to the replay while it is recorded
+            recording: Option<::phi::replay::Replay>,
+            // Where the inputs come from, if not from the player
+            source: Option<Box<dyn InputSource>>,
         }
 
         impl Events {
 
-            pub fn new(pump: EventPump) -> Events {
+            /// Without a `controller_subsystem`, only the keyboard is used.
+            pub fn new(pump: EventPump, controller_subsystem: Option<GameControllerSubsystem>, text_input: TextInputUtil) -> Events {
+                // SDL enables text input by default, which may show a screen
+                // keyboard on some platforms
+                text_input.stop();
+
                 Events {
                     pump: pump,
                     now: ImmediateEvents::new(),
-                    $( $k_alias: false),*
+                    bindings: Bindings::default(),
+                    deadzone: 0.25,
+                    held: HashMap::new(),
+                    step: 0.0,
+                    controller_subsystem,
+                    controllers: Vec::new(),
+                    buttons: HashMap::new(),
+                    axes: HashMap::new(),
+                    mouse_position: (0.0, 0.0),
+                    text_input,
+                    composition: None,
+                    recording: None,
+                    source: None,
+                }
+            }
+
+            /// The position of the cursor in the game area, in logical units.
+            /// It lies outside of the game area when the cursor is over the
+            /// black bars around it.
+            pub fn mouse_position(&self) -> (f64, f64) {
+                self.mouse_position
+            }
+
+            /// Starts collecting the characters typed by the player in
+            /// `now.text`, for instance to enter their name. Keys are still
+            /// reported as usual, so views should ignore the actions bound to
+            /// letters while text input is active.
+            #[cfg(test)]
+            pub fn start_text_input(&mut self) {
+                self.text_input.start();
+            }
+
+            #[cfg(test)]
+            pub fn stop_text_input(&mut self) {
+                self.text_input.stop();
+                self.composition = None;
+            }
+
+            #[cfg(test)]
+            pub fn is_text_input_active(&self) -> bool {
+                self.text_input.is_active()
+            }
+
+            /// The text being composed by an input method, which is not part
+            /// of `now.text` until it is confirmed. It stays available until
+            /// then, rather than only during the frame in which it changed.
+            #[cfg(test)]
+            pub fn composition(&self) -> Option<&str> {
+                self.composition.as_ref().map(|text| &text[..])
+            }
+
+            /// The position of `axis`, from -1 to 1, with the deadzone removed
+            /// and the rest of the range stretched to fill it. When several
+            /// controllers are plugged in, the one pushed the furthest counts.
+            pub fn axis(&self, axis: Axis) -> f64 {
+                let value = self.raw_axis(axis);
+
+                if value.abs() < self.deadzone {
+                    0.0
+                } else {
+                    value.signum() * (value.abs() - self.deadzone) / (1.0 - self.deadzone)
+                }
+            }
+
+            /// How strongly `input` is held, from 0 to 1. Keys and buttons are
+            /// either fully held or not at all.
+            pub fn input_value(&self, input: Input) -> f64 {
+                match input {
+                    Input::Axis(axis, sign) => sign.amount(self.axis(axis)),
+                    _ => if self.held.contains_key(&input) { 1.0 } else { 0.0 },
+                }
+            }
+
+            /// How strongly `action` is held, from 0 to 1, which allows sticks
+            /// to control it gradually.
+            pub fn value(&self, action: Action) -> f64 {
+                self.bindings.inputs(action).iter()
+                    .map(|&input| self.input_value(input))
+                    .fold(0.0, f64::max)
+            }
+
+            /// How long `input` has been held, in seconds, or `None` if it is
+            /// not held. This is 0 during the step in which it is pressed.
+            pub fn input_held_time(&self, input: Input) -> Option<f64> {
+                self.held.get(&input).cloned()
+            }
+
+            /// How long `action` has been held, in seconds, by the input which
+            /// has been held the longest.
+            pub fn held_time(&self, action: Action) -> Option<f64> {
+                self.bindings.inputs(action).iter()
+                    .filter_map(|&input| self.input_held_time(input))
+                    .fold(None, |longest, time| Some(longest.map_or(time, |longest: f64| longest.max(time))))
+            }
+
+            /// How many times the inputs bound to `action` were pressed during
+            /// this frame.
+            pub fn press_count(&self, action: Action) -> usize {
+                self.bindings.inputs(action).iter()
+                    .map(|&input| self.now.press_count(input))
+                    .sum()
+            }
+
+            /// Whether `action` has just been pressed, or has been held for
+            /// `delay` seconds and then for another multiple of `interval`.
+            /// This repeats an action held down, such as moving in a menu.
+            pub fn repeated(&self, action: Action, delay: f64, interval: f64) -> bool {
+                if self.pressed(action) {
+                    return true;
+                }
+
+                match self.held_time(action) {
+                    Some(time) if time >= delay => {
+                        let before = time - self.step;
+                        let repeats = |time: f64| ((time - delay) / interval).floor();
+                        before < delay || repeats(time) > repeats(before)
+                    },
+                    _ => false,
+                }
+            }
+
+            /// Whether an input bound to `action` has just been pressed.
+            pub fn pressed(&self, action: Action) -> bool {
+                self.bindings.inputs(action).iter()
+                    .any(|&input| self.now.input_pressed(input))
+            }
+
+            fn press(&mut self, input: Input) {
+                self.now.pressed.push(input);
+                self.held.entry(input).or_insert(0.0);
+            }
+
+            fn release(&mut self, input: Input) {
+                self.held.remove(&input);
+            }
+
+            // The position of `axis` on the controller which pushes it the
+            // furthest
+            fn raw_axis(&self, axis: Axis) -> f64 {
+                self.axes.iter()
+                    .filter(|&(&(_, other), _)| other == axis)
+                    .map(|(_, &value)| value)
+                    .fold(0.0, |furthest, value| if value.abs() > furthest.abs() { value } else { furthest })
+            }
+
+            fn press_button(&mut self, pad: i32, button: Button) {
+                let first = {
+                    let pads = self.buttons.entry(button).or_default();
+                    if pads.contains(&pad) {
+                        return;
+                    }
+                    pads.push(pad);
+                    pads.len() == 1
+                };
+
+                if first {
+                    self.press(Input::Button(button));
+                }
+            }
+
+            fn release_button(&mut self, pad: i32, button: Button) {
+                let last = match self.buttons.get_mut(&button) {
+                    Some(pads) if pads.contains(&pad) => {
+                        pads.retain(|&other| other != pad);
+                        pads.is_empty()
+                    },
+                    _ => return,
+                };
+
+                if last {
+                    self.buttons.remove(&button);
+                    self.release(Input::Button(button));
+                }
+            }
+
+            // Records the edges of both halves of `axis`, as combined over
+            // every controller, as it moves to `value` on `pad`
+            fn move_axis(&mut self, pad: i32, axis: Axis, value: f64) {
+                let old = self.raw_axis(axis);
+                if value == 0.0 {
+                    self.axes.remove(&(pad, axis));
+                } else {
+                    self.axes.insert((pad, axis), value);
+                }
+                let value = self.raw_axis(axis);
+
+                for &sign in &[Sign::Negative, Sign::Positive] {
+                    let was_down = sign.amount(old) >= AXIS_PRESS_THRESHOLD;
+                    let is_down = sign.amount(value) >= AXIS_PRESS_THRESHOLD;
+
+                    if is_down && !was_down {
+                        self.press(Input::Axis(axis, sign));
+                    } else if was_down && !is_down {
+                        self.release(Input::Axis(axis, sign));
+                    }
+                }
+            }
+
+            /// Starts appending the inputs of every frame to a new replay.
+            pub fn start_recording(&mut self, seed: u64) {
+                self.recording = Some(::phi::replay::Replay::new(seed));
+            }
+
+            /// Stops recording, and returns what was recorded.
+            pub fn stop_recording(&mut self) -> Option<::phi::replay::Replay> {
+                self.recording.take()
+            }
+
+            /// Takes the inputs of the next update steps from `source`, instead
+            /// of from the player, until it runs out. Window events, such as
+            /// quitting, are still taken from the player.
+            pub fn set_source(&mut self, source: Box<dyn InputSource>) {
+                self.source = Some(source);
+            }
+
+            /// Updates the state of the inputs with `event`, as if it had just
+            /// been received from SDL.
+            pub fn apply(&mut self, event: InputEvent) {
+                match event {
+                    InputEvent::KeyDown { key, repeat } => {
+                        if !repeat {
+                            self.press(Input::Key(key));
+                        }
+
+                        if self.text_input.is_active() {
+                            use ::sdl2::keyboard::Keycode::{Backspace, KpEnter, Return};
+
+                            match key {
+                                Backspace => self.now.edits.push(Edit::Backspace),
+                                Return | KpEnter if !repeat => self.now.submit = true,
+                                _ => {}
+                            }
+                        }
+                    },
+                    InputEvent::KeyUp(key) => self.release(Input::Key(key)),
+                    InputEvent::ButtonDown(pad, button) => self.press_button(pad, button),
+                    InputEvent::ButtonUp(pad, button) => self.release_button(pad, button),
+                    InputEvent::AxisMotion(pad, axis, value) => self.move_axis(pad, axis, value),
+                    InputEvent::MouseMotion(x, y) => {
+                        self.mouse_position = (x, y);
+                        self.now.mouse_moved = true;
+                    },
+                    InputEvent::MouseDown(button, x, y) => {
+                        self.mouse_position = (x, y);
+                        self.press(Input::Mouse(button));
+                    },
+                    InputEvent::MouseUp(button, x, y) => {
+                        self.mouse_position = (x, y);
+                        self.release(Input::Mouse(button));
+                    },
+                    InputEvent::Wheel(x, y) => {
+                        self.now.wheel.0 += x;
+                        self.now.wheel.1 += y;
+                    },
+                    InputEvent::Text(text) => {
+                        // The composition, if any, has been confirmed
+                        self.composition = None;
+                        self.now.text.push_str(&text);
+                        self.now.edits.push(Edit::Insert(text));
+                    },
+                    InputEvent::Editing(text) => {
+                        // An empty composition means that it was cancelled
+                        self.composition = if text.is_empty() { None } else { Some(text) };
+                    },
+                    InputEvent::FocusGained => {
+                        self.now.focus_gained = true;
+                    },
+                    InputEvent::FocusLost => {
+                        self.now.focus_lost = true;
+                    },
+                    InputEvent::Minimized => {
+                        self.now.minimized = true;
+                    },
+                    InputEvent::Restored => {
+                        self.now.restored = true;
+                    },
                 }
             }
 
-            pub fn pump(&mut self, renderer: &mut ::sdl2::render::Renderer) {
+            /// Reads the events received since the last call, for an update
+            /// step simulating `elapsed` seconds. `viewport` is used to convert
+            /// the position of the cursor to logical units.
+            ///
+            /// Returns the time which the step should simulate, which is taken
+            /// from the replay while one is playing.
+            pub fn pump(&mut self, renderer: &mut ::sdl2::render::Renderer, viewport: ::phi::window::Viewport, elapsed: f64) -> f64 {
                 self.now = ImmediateEvents::new();
-                for event in self.pump.poll_iter() {
+
+                // The cursor is reported in window coordinates, which differ
+                // from pixels on high-DPI displays
+                let pixel_ratio = match (renderer.window(), renderer.output_size()) {
+                    (Some(window), Ok((output_w, _))) if window.size().0 > 0 =>
+                        output_w as f64 / window.size().0 as f64,
+                    _ => 1.0,
+                };
+                let to_logical = |x: i32, y: i32| {
+                    viewport.to_logical((x as f64 * pixel_ratio, y as f64 * pixel_ratio))
+                };
+
+                // The inputs are gathered before being applied, so that they
+                // can be recorded, or replaced by those of an `InputSource`
+                let mut inputs = Vec::new();
+
+                // Collect the events first, so that `self` may be modified
+                // while handling them
+                let events: Vec<_> = self.pump.poll_iter().collect();
+
+                for event in events {
                     use ::sdl2::event::Event::*;
-                    use ::sdl2::event::WindowEventId::Resized;
-                    use ::sdl2::keyboard::Keycode::*;
+                    use ::sdl2::event::WindowEventId::{FocusGained, FocusLost, Minimized, Restored, SizeChanged};
 
                     match event {
-                        Window { win_event_id: Resized, .. } => {
-                            self.now.resize = Some(renderer.output_size().unwrap());
+                        // Unlike `Resized`, this is also sent when the size is
+                        // changed by the game itself
+                        Window { win_event_id: SizeChanged, .. } => {
+                            self.now.resize = renderer.output_size().ok();
                         },
-                        KeyDown { keycode, .. } => match keycode {
-                            $(
-                                Some($k_sdl) => {
-                                    if !self.$k_alias {
-                                        // Key pressed
-                                        self.now.$k_alias = Some(true);
-                                    }
-                                    self.$k_alias = true;
+                        // These change how the game behaves, and so are
+                        // recorded in replays like the inputs
+                        Window { win_event_id: FocusGained, .. } =>
+                            inputs.push(InputEvent::FocusGained),
+                        Window { win_event_id: FocusLost, .. } =>
+                            inputs.push(InputEvent::FocusLost),
+                        Window { win_event_id: Minimized, .. } =>
+                            inputs.push(InputEvent::Minimized),
+                        Window { win_event_id: Restored, .. } =>
+                            inputs.push(InputEvent::Restored),
+                        KeyDown { keycode: Some(keycode), repeat, .. } =>
+                            inputs.push(InputEvent::KeyDown { key: keycode, repeat }),
+                        KeyUp { keycode: Some(keycode), .. } =>
+                            inputs.push(InputEvent::KeyUp(keycode)),
+                        TextInput { text, .. } =>
+                            inputs.push(InputEvent::Text(text)),
+                        TextEditing { text, .. } =>
+                            inputs.push(InputEvent::Editing(text)),
+
+                        // SDL also sends this for the controllers which are
+                        // already plugged in when the game starts
+                        ControllerDeviceAdded { which, .. } => {
+                            if let Some(ref subsystem) = self.controller_subsystem {
+                                match subsystem.open(which as u32) {
+                                    Ok(controller) => self.controllers.push(controller),
+                                    Err(e) => println!("Could not open controller {}: {:?}", which, e),
                                 }
-                            ),* // comma after every option
-                            _ => {}
+                            }
                         },
-                        KeyUp { keycode, .. } => match keycode {
-                            $(
-                                Some($k_sdl) => {
-                                    // Key released
-                                    self.now.$k_alias = Some(false);
-                                    self.$k_alias = false;
+                        // Unlike when it is added, the controller is identified
+                        // by its instance id
+                        ControllerDeviceRemoved { which, .. } => {
+                            self.controllers.retain(GameController::attached);
+                            // The unplugged controller may have been holding
+                            // some buttons, which will never be released
+                            for (&button, pads) in &self.buttons {
+                                if pads.contains(&which) {
+                                    inputs.push(InputEvent::ButtonUp(which, button));
+                                }
+                            }
+                            for &(pad, axis) in self.axes.keys() {
+                                if pad == which {
+                                    inputs.push(InputEvent::AxisMotion(which, axis, 0.0));
                                 }
-                            ),*
-                            _ => {}
+                            }
+                        },
+                        ControllerButtonDown { which, button, .. } =>
+                            inputs.push(InputEvent::ButtonDown(which, button)),
+                        ControllerButtonUp { which, button, .. } =>
+                            inputs.push(InputEvent::ButtonUp(which, button)),
+                        ControllerAxisMotion { which, axis, value, .. } => {
+                            let value = (value as f64 / 32767.0).clamp(-1.0, 1.0);
+                            inputs.push(InputEvent::AxisMotion(which, axis, value));
+                        },
+
+                        MouseMotion { x, y, .. } => {
+                            let (x, y) = to_logical(x, y);
+                            inputs.push(InputEvent::MouseMotion(x, y));
+                        },
+                        MouseButtonDown { mouse_btn, x, y, .. } => {
+                            let (x, y) = to_logical(x, y);
+                            inputs.push(InputEvent::MouseDown(mouse_btn, x, y));
                         },
+                        MouseButtonUp { mouse_btn, x, y, .. } => {
+                            let (x, y) = to_logical(x, y);
+                            inputs.push(InputEvent::MouseUp(mouse_btn, x, y));
+                        },
+                        MouseWheel { x, y, .. } =>
+                            inputs.push(InputEvent::Wheel(x, y)),
+
                         $(
                             $e_sdl => {
                                 self.now.$e_alias = true;
@@ -87,6 +543,39 @@ macro_rules! struct_events {
                         _ => {}
                     }
                 }
+
+                let mut elapsed = elapsed;
+
+                let frame = self.source.as_mut().map(|source| source.next_frame(elapsed));
+                match frame {
+                    Some(Some(frame)) => {
+                        elapsed = frame.elapsed;
+                        inputs = frame.events;
+                    },
+                    // Give the control back to the player once the source is
+                    // exhausted
+                    Some(None) => self.source = None,
+                    None => {},
+                }
+
+                if let Some(ref mut replay) = self.recording {
+                    replay.frames.push(Frame {
+                        elapsed,
+                        events: inputs.clone(),
+                    });
+                }
+
+                // The inputs still held have been held for one more step
+                self.step = elapsed;
+                for time in self.held.values_mut() {
+                    *time += elapsed;
+                }
+
+                for input in inputs {
+                    self.apply(input);
+                }
+
+                elapsed
             }
         }
     }
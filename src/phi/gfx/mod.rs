@@ -1,8 +1,11 @@
+pub mod text;
+
 use ::phi::data::Rectangle;
-use ::phi::Phi;
+use ::phi::error::{PhiError, PhiResult};
 use ::std::cell::RefCell;
 use ::std::path::Path;
 use ::std::rc::Rc;
+use ::sdl2::pixels::Color;
 use ::sdl2::render::{Renderer, Texture};
 use ::sdl2_image::LoadTexture;
 
@@ -46,13 +49,18 @@ impl Sprite {
         }
     }
 
-    pub fn load(renderer: &Renderer, path: &str) -> Option<Sprite> {
-        renderer.load_texture(Path::new(path)).ok().map(Sprite::new)
+    pub fn load(renderer: &Renderer, path: &str) -> PhiResult<Sprite> {
+        renderer.load_texture(Path::new(path))
+            .map(Sprite::new)
+            .map_err(|e| PhiError::Asset {
+                path: path.to_string(),
+                reason: e.to_string(),
+            })
     }
 
     // Returns a sprite representing sub-region of current
-    // Some if valid, None if invalid
-    pub fn region(&self, rect: Rectangle) -> Option<Sprite> {
+    // Err if the region does not fit inside of the current one
+    pub fn region(&self, rect: Rectangle) -> PhiResult<Sprite> {
         let new_src = Rectangle {
             x: rect.x + self.src.x,
             y: rect.y + self.src.y,
@@ -61,13 +69,13 @@ impl Sprite {
 
         // Verify subregion is inside current
         if self.src.contains(new_src) {
-            Some(Sprite {
+            Ok(Sprite {
                 tex: self.tex.clone(),
                 src: new_src,
             })
         }
         else{
-            None
+            Err(PhiError::InvalidRegion(rect))
         }
     }
 
@@ -76,11 +84,32 @@ impl Sprite {
         (self.src.w, self.src.h)
     }
 
+    // Render the sprite multiplied by color, which is meant for white sprites
+    // such as the glyphs of a font
+    pub fn render_tinted(&self, renderer: &mut Renderer, dest: Rectangle, color: Color) {
+        let (r, g, b, a) = match color {
+            Color::RGB(r, g, b) => (r, g, b, 255),
+            Color::RGBA(r, g, b, a) => (r, g, b, a),
+        };
+
+        if let Some(dest) = dest.to_sdl() {
+            let mut tex = self.tex.borrow_mut();
+            tex.set_color_mod(r, g, b);
+            tex.set_alpha_mod(a);
+            renderer.copy(&tex, self.src.to_sdl(), Some(dest));
+            tex.set_color_mod(255, 255, 255);
+            tex.set_alpha_mod(255);
+        }
+    }
+
 }
 
 impl Renderable for Sprite {
     fn render(&self, renderer: &mut Renderer, dest: Rectangle) {
-        renderer.copy(&mut self.tex.borrow_mut(), self.src.to_sdl(), dest.to_sdl())
+        // SDL would stretch the sprite over the whole screen if given `None`
+        if let Some(dest) = dest.to_sdl() {
+            renderer.copy(&self.tex.borrow_mut(), self.src.to_sdl(), Some(dest))
+        }
     }
 }
 
@@ -97,21 +126,18 @@ pub struct AnimatedSprite {
 }
 
 impl AnimatedSprite {
-    pub fn new(sprites: Vec<Sprite>, frame_delay: f64) -> AnimatedSprite {
-        AnimatedSprite {
-            sprites: Rc::new(sprites),
-            frame_delay: frame_delay,
-            current_time: 0.0,
-        }
-    }
-
-    // Create new animated sprite which goes to next frame fps times per second
-    pub fn with_fps(sprites: Vec<Sprite>, fps: f64) -> AnimatedSprite {
+    // Create new animated sprite which goes to next frame fps times per
+    // second, from frames which may be shared with other animations
+    pub fn from_shared(sprites: Rc<Vec<Sprite>>, fps: f64) -> PhiResult<AnimatedSprite> {
         if fps == 0.0 {
-            panic!("Passed 0 to AnimatedSprite::with_fps");
+            return Err(PhiError::InvalidFps);
         }
 
-        AnimatedSprite::new(sprites, 1.0 / fps)
+        Ok(AnimatedSprite {
+            sprites,
+            frame_delay: 1.0 / fps,
+            current_time: 0.0,
+        })
     }
 
     // Number of frames of the animation
@@ -124,14 +150,6 @@ impl AnimatedSprite {
         self.frame_delay = frame_delay;
     }
 
-    // Set number of frames an animation goes through per second
-    pub fn set_fps(&mut self, fps: f64) {
-        if fps == 0.0 {
-            panic!("Passed 0 to AnimatedSprite::set_fps");
-        }
-        self.set_frame_delay(1.0 / fps);
-    }
-
     pub fn add_time(&mut self, dt: f64) {
         self.current_time += dt;
 
@@ -141,10 +159,8 @@ impl AnimatedSprite {
         }
     }
 
-    // Auto generate an animated sprite from frame description
-    pub fn load_frames(phi: &mut Phi, descr: AnimatedSpriteDescr) -> Vec<Sprite> {
-
-        let spritesheet = Sprite::load(&mut phi.renderer, descr.image_path).unwrap();
+    // Cut the frames described by descr out of a sprite sheet
+    pub fn split_frames(spritesheet: &Sprite, descr: &AnimatedSpriteDescr) -> PhiResult<Vec<Sprite>> {
         let mut frames = Vec::with_capacity(descr.total_frames);
 
         for yth in 0..descr.frames_high {
@@ -159,11 +175,11 @@ impl AnimatedSprite {
                         h: descr.frame_h,
                         x: descr.frame_w * xth as f64,
                         y: descr.frame_h * yth as f64,
-                    }).unwrap());
+                    })?);
             }
         }
 
-        frames
+        Ok(frames)
     }
 }
 
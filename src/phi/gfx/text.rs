@@ -0,0 +1,239 @@
+use ::phi::assets::AssetManager;
+use ::phi::data::Rectangle;
+use ::phi::error::{PhiError, PhiResult};
+use ::phi::gfx::Sprite;
+use ::sdl2::pixels::Color;
+use ::sdl2::render::Renderer;
+use ::std::collections::HashMap;
+
+/// How the lines of a text are placed horizontally inside of their box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// A decoration drawn behind the text to make it readable over any background.
+#[derive(Clone, Copy, Debug)]
+pub enum Effect {
+    None,
+    /// A copy of the text, moved by `offset` pixels.
+    Shadow { offset: (f64, f64), color: Color },
+    /// A border of `width` pixels around every glyph.
+    Outline { width: f64, color: Color },
+}
+
+/// Describes how a text should look.
+#[derive(Clone, Debug)]
+pub struct TextStyle {
+    pub font_path: String,
+    pub size: i32,
+    pub color: Color,
+    pub align: Align,
+    pub effect: Effect,
+    /// Multiplies the height of the lines recommended by the font.
+    pub line_spacing: f64,
+}
+
+impl TextStyle {
+    /// A left-aligned style without effect.
+    pub fn new(font_path: &str, size: i32, color: Color) -> TextStyle {
+        TextStyle {
+            font_path: font_path.to_string(),
+            size,
+            color,
+            align: Align::Left,
+            effect: Effect::None,
+            line_spacing: 1.0,
+        }
+    }
+}
+
+
+/// A character of a font, rendered in white so that it may be tinted to any
+/// color when it is drawn.
+#[derive(Clone)]
+struct Glyph {
+    // Blank characters, such as spaces, have nothing to draw
+    sprite: Option<Sprite>,
+    advance: f64,
+}
+
+/// Keeps the glyphs of every font in memory, so that drawing a text, even one
+/// which changes on every frame, does not create any texture once all of its
+/// characters have been seen.
+pub struct GlyphCache {
+    // The glyphs of every font, by size and character. Fonts are looked up by
+    // path first, so that finding a glyph does not allocate.
+    glyphs: HashMap<String, HashMap<(i32, char), Glyph>>,
+}
+
+impl GlyphCache {
+    pub fn new() -> GlyphCache {
+        GlyphCache {
+            glyphs: HashMap::new(),
+        }
+    }
+
+    /// The number of glyphs in memory, for all fonts and sizes.
+    pub fn len(&self) -> usize {
+        self.glyphs.values().map(HashMap::len).sum()
+    }
+
+    fn glyph(&mut self, assets: &mut AssetManager, renderer: &Renderer, style: &TextStyle, ch: char) -> PhiResult<Glyph> {
+        let cached = self.glyphs.get(&style.font_path[..])
+            .and_then(|font| font.get(&(style.size, ch)));
+
+        if let Some(glyph) = cached {
+            return Ok(glyph.clone());
+        }
+
+        let font = assets.font(&style.font_path, style.size)?;
+
+        let advance = font.metrics_of_char(ch)
+            .map(|metrics| metrics.advance as f64)
+            .unwrap_or(0.0);
+
+        let sprite =
+            if ch.is_whitespace() {
+                None
+            } else {
+                let surface = font.render(ch, ::sdl2_ttf::blended(Color::RGB(255, 255, 255)))
+                    .map_err(|e| PhiError::Font {
+                        path: style.font_path.clone(),
+                        reason: e.0,
+                    })?;
+                Some(Sprite::new(renderer.create_texture_from_surface(&surface)?))
+            };
+
+        let glyph = Glyph {
+            sprite,
+            advance,
+        };
+
+        self.glyphs.entry(style.font_path.clone())
+            .or_default()
+            .insert((style.size, ch), glyph.clone());
+        Ok(glyph)
+    }
+
+    /// Returns the width of a single line of `text`.
+    fn line_width(&mut self, assets: &mut AssetManager, renderer: &Renderer, style: &TextStyle, text: &str) -> PhiResult<f64> {
+        let mut width = 0.0;
+        for ch in text.chars() {
+            width += self.glyph(assets, renderer, style, ch)?.advance;
+        }
+        Ok(width)
+    }
+
+    /// Splits `text` into lines, breaking it at newlines and, if `max_width`
+    /// is given, between words so that no line is wider than it. A word which
+    /// is wider than `max_width` by itself is kept on its own line.
+    pub fn layout(&mut self, assets: &mut AssetManager, renderer: &Renderer, style: &TextStyle, text: &str, max_width: Option<f64>) -> PhiResult<TextLayout> {
+        let font = assets.font(&style.font_path, style.size)?;
+        let line_height = font.line_skip() as f64 * style.line_spacing;
+        let space = self.line_width(assets, renderer, style, " ")?;
+
+        let mut lines = vec![];
+
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            let mut line_width = 0.0;
+
+            for word in paragraph.split(' ') {
+                let word_width = self.line_width(assets, renderer, style, word)?;
+
+                if line.is_empty() {
+                    line.push_str(word);
+                    line_width = word_width;
+                } else if max_width.is_none_or(|max| line_width + space + word_width <= max) {
+                    line.push(' ');
+                    line.push_str(word);
+                    line_width += space + word_width;
+                } else {
+                    lines.push(TextLine { text: line, width: line_width });
+                    line = word.to_string();
+                    line_width = word_width;
+                }
+            }
+
+            lines.push(TextLine { text: line, width: line_width });
+        }
+
+        let width = lines.iter().map(|line| line.width).fold(0.0, f64::max);
+
+        Ok(TextLayout {
+            size: (width, line_height * lines.len() as f64),
+            line_height,
+            lines,
+        })
+    }
+
+    /// Draws `layout` inside of `dest`, aligning each line horizontally.
+    pub fn draw(&mut self, assets: &mut AssetManager, renderer: &mut Renderer, style: &TextStyle, layout: &TextLayout, dest: Rectangle) -> PhiResult<()> {
+        // The effect is drawn first, so that it stays behind the text
+        match style.effect {
+            Effect::None => {},
+
+            Effect::Shadow { offset, color } => {
+                let shadow = Rectangle { x: dest.x + offset.0, y: dest.y + offset.1, ..dest };
+                self.draw_lines(assets, renderer, style, layout, shadow, color)?;
+            },
+
+            Effect::Outline { width, color } => {
+                for &(dx, dy) in &[(-1.0, -1.0), (0.0, -1.0), (1.0, -1.0), (-1.0, 0.0),
+                                   (1.0, 0.0), (-1.0, 1.0), (0.0, 1.0), (1.0, 1.0)] {
+                    let border = Rectangle { x: dest.x + dx * width, y: dest.y + dy * width, ..dest };
+                    self.draw_lines(assets, renderer, style, layout, border, color)?;
+                }
+            },
+        }
+
+        self.draw_lines(assets, renderer, style, layout, dest, style.color)
+    }
+
+    fn draw_lines(&mut self, assets: &mut AssetManager, renderer: &mut Renderer, style: &TextStyle, layout: &TextLayout, dest: Rectangle, color: Color) -> PhiResult<()> {
+        let mut y = dest.y;
+
+        for line in &layout.lines {
+            let mut x = match style.align {
+                Align::Left => dest.x,
+                Align::Center => dest.x + (dest.w - line.width) / 2.0,
+                Align::Right => dest.x + dest.w - line.width,
+            };
+
+            for ch in line.text.chars() {
+                let glyph = self.glyph(assets, renderer, style, ch)?;
+
+                if let Some(ref sprite) = glyph.sprite {
+                    let (w, h) = sprite.size();
+                    sprite.render_tinted(renderer, Rectangle { x, y, w, h }, color);
+                }
+
+                x += glyph.advance;
+            }
+
+            y += layout.line_height;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// A line of text, as split by `GlyphCache::layout`.
+#[derive(Clone, Debug)]
+pub struct TextLine {
+    pub text: String,
+    pub width: f64,
+}
+
+/// The result of splitting a text into lines.
+#[derive(Clone, Debug)]
+pub struct TextLayout {
+    pub lines: Vec<TextLine>,
+    /// The size of the box which contains every line.
+    pub size: (f64, f64),
+    pub line_height: f64,
+}
@@ -0,0 +1,154 @@
+use super::{Events, Phi, View, ViewStack};
+use super::error::{PhiError, PhiResult};
+use super::window::WindowConfig;
+use ::std::sync::{Mutex, MutexGuard};
+
+// SDL may only be initialized once at a time
+static SDL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Runs views without showing anything on the screen, using SDL's dummy video
+/// driver and a software renderer. This makes it possible to exercise the
+/// game on machines which have no display, such as a CI server.
+///
+/// # Examples
+///
+/// ```
+/// let mut headless = Headless::new(800, 600, |phi| {
+///     Ok(Box::new(MainMenuView::new(phi)?))
+/// }).unwrap();
+///
+/// // Simulate one second of the main menu
+/// headless.step(120, UPDATE_INTERVAL);
+/// ```
+pub struct Headless {
+    // The fields are dropped in order: the views must go before the context
+    // which holds the renderer, which itself must go before SDL is shut down.
+    views: ViewStack,
+    pub phi: Phi<'static>,
+    _ttf_context: ::sdl2_ttf::Sdl2TtfContext,
+    _sdl_context: ::sdl2::Sdl,
+    _sdl_lock: MutexGuard<'static, ()>,
+}
+
+impl Headless {
+    /// Creates an offscreen context of size `width`x`height` and starts the
+    /// game with the `View` returned by `init()`. If another `Headless` is
+    /// alive, for instance in a test running in parallel, this waits until
+    /// it is dropped.
+    pub fn new<F>(width: u32, height: u32, init: F) -> PhiResult<Headless>
+    where F: Fn(&mut Phi) -> PhiResult<Box<dyn View>> {
+        // A test which failed while holding the lock has released SDL anyway
+        let sdl_lock = SDL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // The video driver can only be chosen before SDL is initialized
+        ::std::env::set_var("SDL_VIDEODRIVER", "dummy");
+
+        let sdl_context = ::sdl2::init()?;
+        let video = sdl_context.video()?;
+        let ttf_context = ::sdl2_ttf::init().map_err(|e| PhiError::Sdl(e.to_string()))?;
+
+        let config = WindowConfig {
+            width,
+            height,
+            resizable: false,
+            fullscreen_key: None,
+            ..WindowConfig::new("Headless")
+        };
+
+        let window = video.window(&config.title, width, height)
+            .hidden()
+            .build()?;
+
+        let renderer = window.renderer()
+            .software()
+            .build()?;
+
+        let mut phi = Phi::new(
+            Events::new(sdl_context.event_pump()?, sdl_context.game_controller().ok(), video.text_input()),
+            renderer,
+            config)?;
+
+        let initial_view = init(&mut phi)?;
+
+        Ok(Headless {
+            views: ViewStack::new(&mut phi, initial_view),
+            phi,
+            _ttf_context: ttf_context,
+            _sdl_context: sdl_context,
+            _sdl_lock: sdl_lock,
+        })
+    }
+
+    /// Runs `frames` frames of the game, each of which updates the current
+    /// view once with the given `elapsed` time, in seconds, then draws it.
+    ///
+    /// Returns `false` if the game has quit in the meantime.
+    pub fn step(&mut self, frames: usize, elapsed: f64) -> bool {
+        for _ in 0..frames {
+            if !self.is_running() {
+                break;
+            }
+
+            let elapsed = self.phi.pump_events(elapsed);
+            self.views.update(&mut self.phi, elapsed);
+            self.views.draw(&mut self.phi, 0.0);
+            self.phi.renderer.present();
+        }
+
+        self.is_running()
+    }
+
+    /// Whether there remains a view to execute.
+    pub fn is_running(&self) -> bool {
+        !self.views.is_empty()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::phi::{UPDATE_INTERVAL, ViewAction};
+    use ::phi::input::Script;
+    use ::sdl2::keyboard::Keycode;
+    use ::std::cell::Cell;
+    use ::std::rc::Rc;
+
+    // Counts its updates, and quits when Escape is pressed
+    struct CountingView {
+        updates: Rc<Cell<usize>>,
+    }
+
+    impl View for CountingView {
+        fn update(&mut self, phi: &mut Phi, _: f64) -> ViewAction {
+            self.updates.set(self.updates.get() + 1);
+
+            if phi.events.now.key_pressed(Keycode::Escape) {
+                ViewAction::Quit
+            } else {
+                ViewAction::None
+            }
+        }
+
+        fn draw(&self, _: &mut Phi, _: f64) {}
+    }
+
+    #[test]
+    fn step_until_the_view_quits() {
+        let updates = Rc::new(Cell::new(0));
+        let view_updates = updates.clone();
+        let mut headless = Headless::new(320, 240, move |_| {
+            Ok(Box::new(CountingView { updates: view_updates.clone() }))
+        }).unwrap();
+
+        headless.phi.events.set_source(Box::new(Script::new().wait(3).tap(Keycode::Escape)));
+
+        assert!(headless.step(3, UPDATE_INTERVAL));
+        assert_eq!(updates.get(), 3);
+
+        // The view quits on the step which presses Escape, and nothing runs
+        // after that
+        assert!(!headless.step(5, UPDATE_INTERVAL));
+        assert_eq!(updates.get(), 4);
+    }
+}
@@ -0,0 +1,593 @@
+use ::phi::error::{PhiError, PhiResult};
+use ::phi::replay::Frame;
+use ::sdl2::controller::{Axis, Button};
+use ::sdl2::keyboard::Keycode;
+use ::sdl2::mouse::Mouse;
+use ::std::collections::HashMap;
+use ::std::fs::File;
+use ::std::io::{Read, Write};
+
+/// The actions which the player can perform. Views query actions rather than
+/// physical keys, so that the player may choose which inputs trigger them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    NextWeapon,
+    Weapon1,
+    Weapon2,
+    Weapon3,
+    Confirm,
+    Back,
+    Pause,
+}
+
+/// Every action, in the order in which they are saved.
+pub const ACTIONS: [Action; 12] = [
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::Fire,
+    Action::NextWeapon,
+    Action::Weapon1,
+    Action::Weapon2,
+    Action::Weapon3,
+    Action::Confirm,
+    Action::Back,
+    Action::Pause,
+];
+
+impl Action {
+    /// The name of the action in the configuration file.
+    pub fn name(self) -> String {
+        format!("{:?}", self)
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().cloned().find(|action| action.name() == name)
+    }
+}
+
+
+/// The half of a controller's axis which triggers an input, for instance
+/// `Negative` for a stick pushed to the left or to the top.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sign {
+    Negative,
+    Positive,
+}
+
+impl Sign {
+    /// How far an axis is pushed in this direction, from 0 to 1.
+    pub fn amount(self, value: f64) -> f64 {
+        match self {
+            Sign::Negative => (-value).max(0.0),
+            Sign::Positive => value.max(0.0),
+        }
+    }
+}
+
+
+/// A physical input which can be bound to an action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Input {
+    Key(Keycode),
+    Button(Button),
+    Axis(Axis, Sign),
+    Mouse(Mouse),
+}
+
+impl Input {
+    /// The name of the input in the configuration file, such as `Key:Space`,
+    /// `Button:a`, `Axis:-leftx` or `Mouse:Left`.
+    pub fn name(self) -> String {
+        match self {
+            Input::Key(keycode) => format!("Key:{}", keycode.name()),
+            Input::Button(button) => format!("Button:{}", button.string()),
+            Input::Axis(axis, Sign::Negative) => format!("Axis:-{}", axis.string()),
+            Input::Axis(axis, Sign::Positive) => format!("Axis:+{}", axis.string()),
+            Input::Mouse(button) => format!("Mouse:{}", mouse_name(button)),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Input> {
+        let mut parts = name.splitn(2, ':');
+
+        match (parts.next(), parts.next()) {
+            (Some("Key"), Some(key)) => Keycode::from_name(key).map(Input::Key),
+            (Some("Button"), Some(button)) => Button::from_string(button).map(Input::Button),
+            (Some("Axis"), Some(axis)) if axis.starts_with('-') =>
+                Axis::from_string(&axis[1..]).map(|axis| Input::Axis(axis, Sign::Negative)),
+            (Some("Axis"), Some(axis)) if axis.starts_with('+') =>
+                Axis::from_string(&axis[1..]).map(|axis| Input::Axis(axis, Sign::Positive)),
+            (Some("Mouse"), Some(button)) => mouse_from_name(button).map(Input::Mouse),
+            _ => None,
+        }
+    }
+}
+
+/// The name of a mouse button, such as `Left`, or its number if SDL does not
+/// name it.
+pub fn mouse_name(button: Mouse) -> String {
+    match button {
+        Mouse::Unknown(button) => button.to_string(),
+        button => format!("{:?}", button),
+    }
+}
+
+pub fn mouse_from_name(name: &str) -> Option<Mouse> {
+    match name {
+        "Left" => Some(Mouse::Left),
+        "Middle" => Some(Mouse::Middle),
+        "Right" => Some(Mouse::Right),
+        "X1" => Some(Mouse::X1),
+        "X2" => Some(Mouse::X2),
+        _ => name.parse().ok().map(Mouse::from_ll),
+    }
+}
+
+
+/// A change of the state of the inputs, or of the focus of the window, which
+/// does not depend on the size of the window. Positions are given in logical
+/// units, and controllers are identified by the instance id which SDL gives
+/// them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    KeyDown { key: Keycode, repeat: bool },
+    KeyUp(Keycode),
+    ButtonDown(i32, Button),
+    ButtonUp(i32, Button),
+    /// The raw position of an axis, from -1 to 1.
+    AxisMotion(i32, Axis, f64),
+    MouseMotion(f64, f64),
+    MouseDown(Mouse, f64, f64),
+    MouseUp(Mouse, f64, f64),
+    Wheel(i32, i32),
+    Text(String),
+    Editing(String),
+    FocusGained,
+    FocusLost,
+    Minimized,
+    Restored,
+}
+
+
+/// Provides the inputs of the update steps in place of the player, for
+/// instance to play back a replay, to run a scripted test or to let a bot
+/// play. The inputs go through `Events` exactly as if the player had
+/// produced them.
+pub trait InputSource {
+    /// Returns the inputs of the next update step, which was going to
+    /// simulate `elapsed` seconds, and the time it should simulate instead.
+    /// Returns `None` once the source has nothing more to provide, at which
+    /// point the player gets the control back.
+    fn next_frame(&mut self, elapsed: f64) -> Option<Frame>;
+}
+
+/// Closures can drive the game, so that bots may be written inline. They may
+/// share the state of the game through `Rc<RefCell<...>>` to decide what to
+/// do.
+impl<F> InputSource for F where F: FnMut(f64) -> Option<Frame> {
+    fn next_frame(&mut self, elapsed: f64) -> Option<Frame> {
+        self(elapsed)
+    }
+}
+
+
+/// A predefined sequence of inputs, one entry per update step.
+///
+/// ```ignore
+/// // Start a game from the main menu, then fire after one second
+/// let script = Script::new()
+///     .tap(Keycode::Return)
+///     .wait(120)
+///     .tap(Keycode::Space);
+/// phi.events.set_source(Box::new(script));
+/// ```
+#[cfg(test)]
+#[derive(Clone, Debug, Default)]
+pub struct Script {
+    frames: ::std::collections::VecDeque<Vec<InputEvent>>,
+}
+
+#[cfg(test)]
+impl Script {
+    pub fn new() -> Script {
+        Script {
+            frames: ::std::collections::VecDeque::new(),
+        }
+    }
+
+    /// Adds a step during which `events` happen.
+    pub fn then(mut self, events: Vec<InputEvent>) -> Script {
+        self.frames.push_back(events);
+        self
+    }
+
+    /// Adds `steps` steps without any input.
+    pub fn wait(mut self, steps: usize) -> Script {
+        for _ in 0..steps {
+            self.frames.push_back(Vec::new());
+        }
+        self
+    }
+
+    /// Presses `key` for one step, then releases it.
+    pub fn tap(self, key: Keycode) -> Script {
+        self.hold(key, 1)
+    }
+
+    /// Presses `key` for `steps` steps, then releases it.
+    pub fn hold(self, key: Keycode, steps: usize) -> Script {
+        self.then(vec![InputEvent::KeyDown { key, repeat: false }])
+            .wait(steps.saturating_sub(1))
+            .then(vec![InputEvent::KeyUp(key)])
+    }
+
+    /// The number of steps which remain to be played.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+#[cfg(test)]
+impl InputSource for Script {
+    fn next_frame(&mut self, elapsed: f64) -> Option<Frame> {
+        self.frames.pop_front().map(|events| Frame {
+            elapsed,
+            events,
+        })
+    }
+}
+
+
+/// Associates every action with the inputs which trigger it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    inputs: HashMap<Action, Vec<Input>>,
+}
+
+impl Bindings {
+    /// Bindings without any input.
+    pub fn empty() -> Bindings {
+        Bindings {
+            inputs: HashMap::new(),
+        }
+    }
+
+    /// The inputs which trigger `action`.
+    pub fn inputs(&self, action: Action) -> &[Input] {
+        self.inputs.get(&action).map(|inputs| &inputs[..]).unwrap_or(&[])
+    }
+
+    /// Makes `input` trigger `action`, in addition to its other inputs.
+    pub fn bind(&mut self, action: Action, input: Input) {
+        let inputs = self.inputs.entry(action).or_default();
+        if !inputs.contains(&input) {
+            inputs.push(input);
+        }
+    }
+
+    /// Stops `input` from triggering `action`.
+    #[cfg(test)]
+    pub fn unbind(&mut self, action: Action, input: Input) {
+        if let Some(inputs) = self.inputs.get_mut(&action) {
+            inputs.retain(|&other| other != input);
+        }
+    }
+
+    /// Removes every input of `action`.
+    #[cfg(test)]
+    pub fn clear(&mut self, action: Action) {
+        self.inputs.remove(&action);
+    }
+
+    /// Reads bindings from the file at `path`. Each line binds an input to an
+    /// action, such as `Fire = Key:Space`. Empty lines and lines starting with
+    /// `#` are ignored.
+    pub fn load(path: &str) -> PhiResult<Bindings> {
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|e| config_error(path, e.to_string()))?;
+
+        let mut bindings = Bindings::empty();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=').map(str::trim);
+            let (action, input) = match (parts.next(), parts.next()) {
+                (Some(action), Some(input)) => (action, input),
+                _ => return Err(config_error(path, format!("line {}: expected `Action = Input`", number + 1))),
+            };
+
+            let action = Action::from_name(action)
+                .ok_or_else(|| config_error(path, format!("line {}: unknown action `{}`", number + 1, action)))?;
+            let input = Input::from_name(input)
+                .ok_or_else(|| config_error(path, format!("line {}: unknown input `{}`", number + 1, input)))?;
+
+            bindings.bind(action, input);
+        }
+
+        Ok(bindings)
+    }
+
+    /// Writes the bindings to the file at `path`, in the format read by `load`.
+    pub fn save(&self, path: &str) -> PhiResult<()> {
+        let mut contents = String::from("# Controls: one `Action = Input` per line\n");
+
+        for &action in ACTIONS.iter() {
+            for &input in self.inputs(action) {
+                contents.push_str(&format!("{} = {}\n", action.name(), input.name()));
+            }
+        }
+
+        File::create(path)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .map_err(|e| config_error(path, e.to_string()))
+    }
+}
+
+impl Default for Bindings {
+    /// The controls used when the player has not chosen their own.
+    fn default() -> Bindings {
+        use ::sdl2::keyboard::Keycode::*;
+
+        let mut bindings = Bindings::empty();
+        for &(action, key) in &[
+            (Action::MoveUp, Up), (Action::MoveUp, W),
+            (Action::MoveDown, Down), (Action::MoveDown, S),
+            (Action::MoveLeft, Left), (Action::MoveLeft, A),
+            (Action::MoveRight, Right), (Action::MoveRight, D),
+            (Action::Fire, Space),
+            (Action::NextWeapon, Tab),
+            (Action::Weapon1, Num1),
+            (Action::Weapon2, Num2),
+            (Action::Weapon3, Num3),
+            (Action::Confirm, Return), (Action::Confirm, Space),
+            (Action::Back, Escape),
+            (Action::Pause, Return), (Action::Pause, P),
+        ] {
+            bindings.bind(action, Input::Key(key));
+        }
+
+        for &(action, button) in &[
+            (Action::MoveUp, Button::DPadUp),
+            (Action::MoveDown, Button::DPadDown),
+            (Action::MoveLeft, Button::DPadLeft),
+            (Action::MoveRight, Button::DPadRight),
+            (Action::Fire, Button::A),
+            (Action::NextWeapon, Button::RightShoulder),
+            (Action::Confirm, Button::A),
+            (Action::Back, Button::B),
+            (Action::Pause, Button::Start),
+        ] {
+            bindings.bind(action, Input::Button(button));
+        }
+
+        for &(action, axis, sign) in &[
+            (Action::MoveUp, Axis::LeftY, Sign::Negative),
+            (Action::MoveDown, Axis::LeftY, Sign::Positive),
+            (Action::MoveLeft, Axis::LeftX, Sign::Negative),
+            (Action::MoveRight, Axis::LeftX, Sign::Positive),
+        ] {
+            bindings.bind(action, Input::Axis(axis, sign));
+        }
+
+        bindings
+    }
+}
+
+fn config_error(path: &str, reason: String) -> PhiError {
+    PhiError::Config {
+        path: path.to_string(),
+        reason,
+    }
+}
+
+
+/// A change made to the text typed by the player.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    /// Characters were typed, or confirmed by an input method.
+    Insert(String),
+    /// The last character was erased.
+    Backspace,
+}
+
+
+/// A line of text typed by the player, such as a name for the high scores.
+/// Text input must be started on `Events` for the field to receive any
+/// character.
+#[cfg(test)]
+#[derive(Clone, Debug, Default)]
+pub struct TextField {
+    pub text: String,
+    /// The maximum number of characters, if any.
+    pub max_len: Option<usize>,
+}
+
+#[cfg(test)]
+impl TextField {
+    pub fn new(max_len: Option<usize>) -> TextField {
+        TextField {
+            text: String::new(),
+            max_len,
+        }
+    }
+
+    /// Applies the characters typed and erased during the frame. Returns
+    /// whether the text was submitted.
+    pub fn update(&mut self, now: &::phi::ImmediateEvents) -> bool {
+        for edit in &now.edits {
+            self.apply(edit);
+        }
+
+        now.submit
+    }
+
+    /// Applies a single edit to the text.
+    pub fn apply(&mut self, edit: &Edit) {
+        match *edit {
+            Edit::Insert(ref text) => {
+                for c in text.chars() {
+                    if self.max_len.is_none_or(|max| self.text.chars().count() < max) {
+                        self.text.push(c);
+                    }
+                }
+            },
+            Edit::Backspace => {
+                self.text.pop();
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::phi::{Phi, View, ViewAction, UPDATE_INTERVAL, temp_path};
+    use ::phi::headless::Headless;
+    use ::std::cell::RefCell;
+    use ::std::fs;
+    use ::std::rc::Rc;
+
+    #[test]
+    fn bindings_save_then_load() {
+        let mut bindings = Bindings::default();
+        bindings.unbind(Action::Fire, Input::Key(Keycode::Space));
+        bindings.bind(Action::Fire, Input::Key(Keycode::LCtrl));
+        bindings.bind(Action::Fire, Input::Mouse(Mouse::Left));
+        bindings.bind(Action::NextWeapon, Input::Axis(Axis::TriggerRight, Sign::Positive));
+        bindings.clear(Action::Pause);
+
+        let path = temp_path("controls.cfg");
+        bindings.save(&path).unwrap();
+        let loaded = Bindings::load(&path);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        for &action in ACTIONS.iter() {
+            assert_eq!(loaded.inputs(action), bindings.inputs(action), "{:?}", action);
+        }
+        assert!(loaded.inputs(Action::Pause).is_empty());
+    }
+
+    #[test]
+    fn bindings_load_rejects_invalid_lines() {
+        let path = temp_path("invalid-controls.cfg");
+
+        for contents in &["Fire Key:Space\n", "Jump = Key:Space\n", "Fire = Key:NotAKey\n", "Fire = Axis:leftx\n"] {
+            fs::write(&path, contents).unwrap();
+            assert!(Bindings::load(&path).is_err(), "{:?}", contents);
+        }
+
+        fs::write(&path, "# Comment\n\n  Fire = Button:a  \n").unwrap();
+        let bindings = Bindings::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(bindings.unwrap().inputs(Action::Fire), &[Input::Button(Button::A)]);
+    }
+
+    #[test]
+    fn input_names() {
+        for &input in &[Input::Key(Keycode::Space), Input::Button(Button::Start),
+                        Input::Axis(Axis::LeftY, Sign::Negative), Input::Mouse(Mouse::Right)] {
+            assert_eq!(Input::from_name(&input.name()), Some(input));
+        }
+        for &action in ACTIONS.iter() {
+            assert_eq!(Action::from_name(&action.name()), Some(action));
+        }
+    }
+
+    #[test]
+    fn text_field_applies_edits_in_order() {
+        let mut field = TextField::new(None);
+        for edit in &[Edit::Insert("ab".to_string()), Edit::Backspace,
+                      Edit::Insert("c\u{e9}".to_string()), Edit::Backspace, Edit::Backspace] {
+            field.apply(edit);
+        }
+        assert_eq!(field.text, "a");
+
+        // Erasing an empty field does nothing
+        field.apply(&Edit::Backspace);
+        field.apply(&Edit::Backspace);
+        assert_eq!(field.text, "");
+    }
+
+    #[test]
+    fn text_field_max_len_counts_characters() {
+        let mut field = TextField::new(Some(3));
+        field.apply(&Edit::Insert("\u{e9}\u{e9}\u{e9}\u{e9}".to_string()));
+        assert_eq!(field.text, "\u{e9}\u{e9}\u{e9}");
+
+        field.apply(&Edit::Backspace);
+        field.apply(&Edit::Insert("xy".to_string()));
+        assert_eq!(field.text, "\u{e9}\u{e9}x");
+    }
+
+    // Types a name while text input is active, and quits once it is submitted
+    struct NameView {
+        field: TextField,
+        name: Rc<RefCell<Option<String>>>,
+    }
+
+    impl View for NameView {
+        fn update(&mut self, phi: &mut Phi, _: f64) -> ViewAction {
+            if self.field.update(&phi.events.now) {
+                *self.name.borrow_mut() = Some(self.field.text.clone());
+                return ViewAction::Quit;
+            }
+            ViewAction::None
+        }
+
+        fn draw(&self, _: &mut Phi, _: f64) {}
+
+        fn enter(&mut self, phi: &mut Phi) {
+            phi.events.start_text_input();
+        }
+
+        fn exit(&mut self, phi: &mut Phi) {
+            phi.events.stop_text_input();
+        }
+    }
+
+    #[test]
+    fn text_input_fills_a_text_field() {
+        let name = Rc::new(RefCell::new(None));
+        let view_name = name.clone();
+        let mut headless = Headless::new(320, 240, move |_| {
+            Ok(Box::new(NameView {
+                field: TextField::new(Some(5)),
+                name: view_name.clone(),
+            }))
+        }).unwrap();
+        assert!(headless.phi.events.is_text_input_active());
+
+        let script = Script::new()
+            .then(vec![InputEvent::Editing("Jo".to_string())])
+            .then(vec![InputEvent::Text("Jo".to_string())])
+            .then(vec![InputEvent::Text("hnny".to_string())])
+            .tap(Keycode::Backspace)
+            .tap(Keycode::Return);
+        headless.phi.events.set_source(Box::new(script));
+
+        // The composition is not part of the text until it is confirmed
+        assert!(headless.step(1, UPDATE_INTERVAL));
+        assert_eq!(headless.phi.events.composition(), Some("Jo"));
+        assert!(headless.step(1, UPDATE_INTERVAL));
+        assert_eq!(headless.phi.events.composition(), None);
+
+        assert!(!headless.step(10, UPDATE_INTERVAL));
+        assert_eq!(*name.borrow(), Some("John".to_string()));
+        assert!(!headless.phi.events.is_text_input_active());
+    }
+}
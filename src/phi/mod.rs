@@ -1,29 +1,32 @@
 #[macro_use]
 mod events;
+pub mod assets;
+pub mod collision;
 pub mod data;
+pub mod error;
 pub mod gfx;
-
-use self::gfx::Sprite;
-use ::sdl2::render::Renderer;
+pub mod headless;
+pub mod input;
+pub mod random;
+pub mod replay;
+pub mod stats;
+pub mod window;
+
+use self::assets::AssetManager;
+use self::gfx::{AnimatedSprite, AnimatedSpriteDescr, Sprite};
+use self::gfx::text::{GlyphCache, TextStyle};
+use self::data::Rectangle;
+use self::error::{PhiError, PhiResult};
+use self::random::Random;
+use self::replay::{Playback, Replay};
+use self::stats::FrameStats;
+use self::window::{Scaling, Viewport, WindowConfig, WindowMode};
+use ::sdl2::keyboard::Keycode;
+use ::sdl2::render::{BlendMode, Renderer};
 use ::sdl2::pixels::Color;
-use ::std::collections::HashMap;
-use ::std::path::Path;
 
 
 struct_events! {
-    keyboard: {
-        key_escape: Escape,
-        key_up: Up,
-        key_down: Down,
-        key_left: Left,
-        key_right: Right,
-        key_space: Space,
-        key_enter: Return,
-
-        key_1: Num1,
-        key_2: Num2,
-        key_3: Num3
-    },
     else: {
         quit: Quit { .. }
     }
@@ -35,71 +38,487 @@ struct_events! {
 pub struct Phi<'window> {
     pub events: Events,
     pub renderer: Renderer<'window>,
-
-    cached_fonts: HashMap<(&'static str, i32), ::sdl2_ttf::Font>,
+    pub assets: AssetManager,
+    pub glyphs: GlyphCache,
+    /// The randomness of the game, seeded from the seed of the run.
+    pub rng: Random,
+
+    window_config: WindowConfig,
+    viewport: Viewport,
+    // Whether the views must be notified that the game area was resized
+    resized: bool,
+
+    stats: FrameStats,
+    // The style used to draw the statistics, if the overlay is enabled
+    stats_style: Option<TextStyle>,
+    show_stats: bool,
+    stats_text: String,
+
+    // The seed of the current run, and where to save its replay if it is
+    // being recorded
+    seed: u64,
+    replay_path: Option<String>,
 }
 
 impl<'window> Phi<'window> {
-    fn new(events: Events, renderer: Renderer<'window>) -> Phi<'window> {
+    fn new(events: Events, renderer: Renderer<'window>, window_config: WindowConfig) -> PhiResult<Phi<'window>> {
         ::sdl2_image::init(::sdl2_image::INIT_PNG);
 
-        Phi {
+        let seed = ::rand::random();
+
+        let mut phi = Phi {
             events: events,
             renderer: renderer,
-            cached_fonts: HashMap::new(),
-        }
+            assets: AssetManager::new(),
+            glyphs: GlyphCache::new(),
+            rng: Random::new(seed),
+            window_config,
+            viewport: Viewport::new((0.0, 0.0), None, Scaling::Fit),
+            resized: false,
+            stats: FrameStats::new(),
+            stats_style: None,
+            show_stats: false,
+            stats_text: String::new(),
+            seed,
+            replay_path: None,
+        };
+
+        phi.update_viewport()?;
+        Ok(phi)
     }
 
+    /// Returns the size of the game area, in logical units, as of the last
+    /// call to `pump_events`. This does not query the renderer, and so may be
+    /// used freely during `View::update`.
     pub fn output_size(&self) -> (f64, f64) {
-        let (w, h) = self.renderer.output_size().unwrap();
-        (w as f64, h as f64)
+        self.viewport.size
     }
 
+    /// Scales and centers the logical game area inside of the window.
+    fn update_viewport(&mut self) -> PhiResult<()> {
+        let (w, h) = self.renderer.output_size()?;
 
-    /// Renders a string of text as a sprite using the provided parameters.
-    pub fn ttf_str_sprite(&mut self, text: &str, font_path: &'static str, size: i32, color: Color) -> Option<Sprite> {
-        if let Some(font) = self.cached_fonts.get(&(font_path, size)) {
-            return font.render(text, ::sdl2_ttf::blended(color)).ok()
-                .and_then(|surface| self.renderer.create_texture_from_surface(&surface).ok())
-                .map(Sprite::new)
+        // A minimized window has no size, which would make the scale 0. The
+        // previous viewport is kept until the window is restored.
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+
+        self.viewport = Viewport::new(
+            (w as f64, h as f64),
+            self.window_config.logical_size,
+            self.window_config.scaling);
+
+        // The position of the viewport is expressed in scaled units
+        let scale = self.viewport.scale;
+        let (logical_w, logical_h) = self.viewport.size;
+
+        self.renderer.set_scale(scale as f32, scale as f32);
+        self.renderer.set_viewport(Rectangle {
+            x: self.viewport.offset.0 / scale,
+            y: self.viewport.offset.1 / scale,
+            w: logical_w,
+            h: logical_h,
+        }.to_sdl());
+
+        // Prevent sprites which overflow the game area from being drawn over
+        // the black bars
+        self.renderer.set_clip_rect(Rectangle::with_size(logical_w, logical_h).to_sdl());
+        Ok(())
+    }
+
+    /// Returns whether the game area was resized since the last call, so that
+    /// each resize is only reported to the views once.
+    fn take_resized(&mut self) -> bool {
+        ::std::mem::replace(&mut self.resized, false)
+    }
+
+    /// Updates the events record and keeps track of the size of the window.
+    /// Returns the time which the next update step should simulate, which
+    /// differs from `elapsed` while a replay is playing.
+    fn pump_events(&mut self, elapsed: f64) -> f64 {
+        let elapsed = self.events.pump(&mut self.renderer, self.viewport, elapsed);
+
+        if let Some(key) = self.window_config.fullscreen_key {
+            if self.events.now.key_pressed(key) {
+                // The hotkey should not prevent the game from running
+                if let Err(e) = self.toggle_fullscreen() {
+                    println!("Could not toggle fullscreen: {}", e);
+                }
+            }
+        }
+
+        if self.events.now.key_pressed(STATS_OVERLAY_KEY) {
+            self.toggle_stats_overlay();
+        }
+
+        if self.events.now.resize.is_some() {
+            // Keep the previous viewport if the new one cannot be computed
+            if let Err(e) = self.update_viewport() {
+                println!("Could not resize the game area: {}", e);
+            }
+            self.resized = true;
         }
 
-        ::sdl2_ttf::Font::from_file(Path::new(font_path), size).ok()
-            .and_then(|font| {
-                self.cached_fonts.insert((font_path, size), font);
-                self.ttf_str_sprite(text, font_path, size, color)
-            })
+        elapsed
+    }
+
+    /// Changes the settings of the window while the game is running. If the
+    /// size of the window changes as a result, the views are notified through
+    /// `View::resize` before their next update.
+    pub fn set_window_config(&mut self, config: WindowConfig) -> PhiResult<()> {
+        if let Some(window) = self.renderer.window_mut() {
+            config.apply(&self.window_config, window)?;
+        }
+
+        // A change of the window's size will be noticed when pumping events,
+        // but a change of the logical size must be handled right away
+        let rescaled =
+            config.logical_size != self.window_config.logical_size ||
+            config.scaling != self.window_config.scaling;
+
+        self.window_config = config;
+
+        if rescaled {
+            self.update_viewport()?;
+            self.resized = true;
+        }
+
+        Ok(())
+    }
+
+    /// Switches between windowed and fullscreen mode, keeping the resolution
+    /// of the desktop.
+    pub fn toggle_fullscreen(&mut self) -> PhiResult<()> {
+        let mode = match self.window_config.mode {
+            WindowMode::Windowed => WindowMode::DesktopFullscreen,
+            WindowMode::Fullscreen | WindowMode::DesktopFullscreen => WindowMode::Windowed,
+        };
+
+        self.set_window_config(WindowConfig {
+            mode,
+            ..self.window_config.clone()
+        })
+    }
+
+    /// Starts the run over from `seed`, for instance so that every player of
+    /// a daily challenge faces the same game. This should be called before
+    /// the views are created, so that they draw from the new `rng`.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Random::new(seed);
+    }
+
+    /// Records the inputs of every update step from now on, and saves them
+    /// with the seed to `path` when the recording stops or the game closes.
+    pub fn start_recording(&mut self, path: &str) {
+        self.events.start_recording(self.seed);
+        self.replay_path = Some(path.to_string());
+    }
+
+    /// Stops recording the inputs, and saves the replay. Does nothing if no
+    /// recording is in progress.
+    pub fn stop_recording(&mut self) -> PhiResult<()> {
+        match (self.events.stop_recording(), self.replay_path.take()) {
+            (Some(replay), Some(path)) => replay.save(&path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Plays back `replay` instead of the player's inputs, and restores the
+    /// seed it was recorded with. This should be called before the views are
+    /// created, so that they see the same seed as in the recorded run.
+    pub fn play_replay(&mut self, replay: Replay) {
+        self.set_seed(replay.seed);
+        self.events.set_source(Box::new(Playback::new(replay)));
+    }
+
+    /// Allows the statistics to be drawn over the game with `font_path`,
+    /// which is done by pressing F3.
+    pub fn enable_stats_overlay(&mut self, font_path: &str) {
+        self.stats_style = Some(TextStyle::new(font_path, 16, Color::RGB(255, 255, 255)));
+    }
+
+    /// Shows or hides the statistics, if the overlay is enabled.
+    pub fn toggle_stats_overlay(&mut self) {
+        self.show_stats = !self.show_stats && self.stats_style.is_some();
+        self.refresh_stats_overlay();
+    }
+
+    /// Records the timing of a frame, refreshing the overlay when needed.
+    fn record_frame(&mut self, frame_time: f64, update_time: f64, updates: u32, render_time: f64) {
+        if self.stats.record(frame_time, update_time, updates, render_time, FRAME_INTERVAL) {
+            self.refresh_stats_overlay();
+        }
+    }
+
+    /// Updates the text of the overlay. This is only done once per second, so
+    /// that it can be read.
+    fn refresh_stats_overlay(&mut self) {
+        let (images, fonts) = self.assets.len();
+        let mut lines = self.stats.lines();
+        lines.push(format!("Assets: {} images ({:.1} MB), {} fonts, {} glyphs",
+            images, self.assets.memory_usage() as f64 / 1_000_000.0, fonts, self.glyphs.len()));
+
+        self.stats_text = lines.join("\n");
+    }
+
+    /// Draws the statistics in the top-left corner of the game area.
+    fn draw_stats_overlay(&mut self) {
+        let style = match self.stats_style {
+            Some(ref style) if self.show_stats => style.clone(),
+            _ => return,
+        };
+
+        let text = self.stats_text.clone();
+        let margin = 6.0;
+
+        let (w, h) = match self.measure_text(&text, &style, None) {
+            Ok(size) => size,
+            Err(e) => {
+                println!("Could not draw the frame statistics: {}", e);
+                self.show_stats = false;
+                return;
+            },
+        };
+
+        self.renderer.set_blend_mode(BlendMode::Blend);
+        self.renderer.set_draw_color(Color::RGBA(0, 0, 0, 180));
+        if let Some(rect) = Rectangle::with_size(w + margin * 2.0, h + margin * 2.0).to_sdl() {
+            self.renderer.fill_rect(rect);
+        }
+        self.renderer.set_blend_mode(BlendMode::None);
+
+        let drawn = self.draw_text(&text, &style, Rectangle {
+            x: margin,
+            y: margin,
+            w,
+            h,
+        });
+        if let Err(e) = drawn {
+            println!("Could not draw the frame statistics: {}", e);
+            self.show_stats = false;
+        }
+    }
+
+    /// Returns the size of the box which `text` occupies when drawn with
+    /// `style`, wrapping its lines if they are wider than `max_width`.
+    pub fn measure_text(&mut self, text: &str, style: &TextStyle, max_width: Option<f64>) -> PhiResult<(f64, f64)> {
+        self.glyphs.layout(&mut self.assets, &self.renderer, style, text, max_width)
+            .map(|layout| layout.size)
+    }
+
+    /// Draws `text` inside of `dest`, wrapping the lines which are wider than
+    /// it. Unlike `ttf_str_sprite`, this reuses the glyphs which were already
+    /// rendered, which makes it suitable for text which changes often.
+    pub fn draw_text(&mut self, text: &str, style: &TextStyle, dest: Rectangle) -> PhiResult<()> {
+        let layout = self.glyphs.layout(&mut self.assets, &self.renderer, style, text, Some(dest.w))?;
+        self.glyphs.draw(&mut self.assets, &mut self.renderer, style, &layout, dest)
+    }
+
+    /// Returns the image at `path` as a sprite, loading it only if it was not
+    /// already in the asset manager.
+    pub fn load_sprite(&mut self, path: &str) -> PhiResult<Sprite> {
+        self.assets.sprite(&self.renderer, path)
+    }
+
+    /// Returns the animation described by `descr`, loading its frames only if
+    /// they were not already in the asset manager.
+    pub fn load_animation(&mut self, descr: AnimatedSpriteDescr, fps: f64) -> PhiResult<AnimatedSprite> {
+        self.assets.animation(&self.renderer, descr, fps)
+    }
+
+    /// Renders a string of text as a sprite using the provided parameters.
+    pub fn ttf_str_sprite(&mut self, text: &str, font_path: &str, size: i32, color: Color) -> PhiResult<Sprite> {
+        let font = self.assets.font(font_path, size)?;
+        let surface = font.render(text, ::sdl2_ttf::blended(color))
+            .map_err(|e| PhiError::Font {
+                path: font_path.to_string(),
+                reason: e.0,
+            })?;
+
+        let texture = self.renderer.create_texture_from_surface(&surface)?;
+        Ok(Sprite::new(texture))
     }
 }
 
 impl<'window> Drop for Phi<'window> {
     fn drop(&mut self) {
+        // Keep what was recorded when the game is closed
+        if let Err(e) = self.stop_recording() {
+            println!("Could not save the replay: {}", e);
+        }
+
         ::sdl2_image::quit();
     }
 }
 
 
+/// The duration which the game loop aims for each frame to have, in seconds.
+const FRAME_INTERVAL: f64 = 1.0 / 60.0;
+
+/// The key which shows or hides the frame statistics, when they are enabled
+/// through `Phi::enable_stats_overlay`.
+const STATS_OVERLAY_KEY: Keycode = Keycode::F3;
+
+/// The duration of a single simulation step, in seconds. Views are updated at
+/// this fixed rate, whatever the frame rate of the machine running the game.
+pub const UPDATE_INTERVAL: f64 = 1.0 / 120.0;
+
+/// The longest duration, in seconds, which may be simulated during a single
+/// frame. If the game falls further behind (for example, while the window is
+/// being dragged), the extra time is dropped rather than caught up with.
+const MAX_FRAME_TIME: f64 = 0.25;
+
+
 /// A `ViewAction` is a way for the currently executed view to
 /// communicate with the game loop. It specifies which action
 /// should be executed before the next rendering.
 pub enum ViewAction {
     None,
     Quit,
-    ChangeView(Box<View>),
+    /// Place a view on top of the current one, which is kept alive.
+    PushView(Box<dyn View>),
+    /// Destroy the current view and go back to the one below it. The game
+    /// stops if there is no such view.
+    PopView,
 }
 
 
+/// A `View` is split in two phases. `update` handles the input and advances
+/// the simulation, while `draw` shows the result on the screen.
+///
+/// Because all of the state is modified by `update`, it may be called any
+/// number of times between two frames, or without ever drawing anything,
+/// which is useful to fast-forward the game or to test it.
 pub trait View {
-    /// Called on every frame to take care of both the logic and
-    /// the rendering of the current view.
+    /// Called at a fixed rate to take care of the logic of the current view.
+    /// It must not draw anything, so it should not use `context.renderer`.
     ///
-    /// `elapsed` is expressed in seconds.
-    fn render(&mut self, context: &mut Phi, elapsed: f64) -> ViewAction;
+    /// `elapsed` is expressed in seconds and is always `UPDATE_INTERVAL`
+    /// when called by `spawn`.
+    fn update(&mut self, context: &mut Phi, elapsed: f64) -> ViewAction;
+
+    /// Called once per frame to render the current view. It only reads the
+    /// state of the view.
+    ///
+    /// `lerp`, between 0 and 1, is the fraction of a simulation step which
+    /// has elapsed since the last call to `update`. It may be used to
+    /// interpolate the position of moving objects.
+    fn draw(&self, context: &mut Phi, lerp: f64);
+
+    /// Whether the view below this one in the stack should be drawn before
+    /// it, for example because this view is a pause screen or a dialog.
+    fn is_transparent(&self) -> bool {
+        false
+    }
+
+    /// Called when the view is placed on the stack, before its first update.
+    fn enter(&mut self, _context: &mut Phi) {}
+
+    /// Called when the view is removed from the stack, right before it is
+    /// dropped. This also happens to every view when the game quits.
+    fn exit(&mut self, _context: &mut Phi) {}
+
+    /// Called when another view is pushed on top of this one. The view will
+    /// not be updated until it is resumed.
+    fn pause(&mut self, _context: &mut Phi) {}
+
+    /// Called when the view above this one has been popped, so that this view
+    /// is on top of the stack again.
+    fn resume(&mut self, _context: &mut Phi) {}
+
+    /// Called when the size of the game area has changed, before the next
+    /// update. The new size is given by `context.output_size()`.
+    fn resize(&mut self, _context: &mut Phi) {}
+}
+
+
+/// The views which are currently alive. Only the view on top of the stack is
+/// updated, but the ones below are still drawn when the views covering them
+/// are transparent.
+struct ViewStack {
+    views: Vec<Box<dyn View>>,
+}
+
+impl ViewStack {
+    fn new(context: &mut Phi, mut initial: Box<dyn View>) -> ViewStack {
+        initial.enter(context);
+
+        ViewStack {
+            views: vec![initial],
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+
+    /// Updates the view on top of the stack and executes the action that it
+    /// returns.
+    fn update(&mut self, context: &mut Phi, elapsed: f64) {
+        // Every view is notified, so that those below the current one are laid
+        // out correctly once they are resumed
+        if context.take_resized() {
+            for view in &mut self.views {
+                view.resize(context);
+            }
+        }
+
+        let action = match self.views.last_mut() {
+            Some(view) => view.update(context, elapsed),
+            None => return,
+        };
+
+        match action {
+            ViewAction::None => {},
+
+            ViewAction::Quit => {
+                while let Some(mut view) = self.views.pop() {
+                    view.exit(context);
+                }
+            },
+
+            ViewAction::PushView(mut new_view) => {
+                if let Some(old_view) = self.views.last_mut() {
+                    old_view.pause(context);
+                }
+                new_view.enter(context);
+                self.views.push(new_view);
+            },
+
+            ViewAction::PopView => {
+                if let Some(mut old_view) = self.views.pop() {
+                    old_view.exit(context);
+                }
+                if let Some(view) = self.views.last_mut() {
+                    view.resume(context);
+                }
+            },
+        }
+    }
+
+    /// Draws the view on top of the stack, preceded by all of the views which
+    /// can be seen through it.
+    fn draw(&self, context: &mut Phi, lerp: f64) {
+        if self.views.is_empty() {
+            return;
+        }
+
+        let mut bottom = self.views.len() - 1;
+        while bottom > 0 && self.views[bottom].is_transparent() {
+            bottom -= 1;
+        }
+
+        for view in &self.views[bottom..] {
+            view.draw(context, lerp);
+        }
+    }
 }
 
 
-/// Create a window with name `title`, initialize the underlying libraries and
-/// start the game with the `View` returned by `init()`.
+/// Create a window using the settings in `config`, initialize the underlying
+/// libraries and start the game with the `View` returned by `init()`.
 ///
 /// # Examples
 ///
@@ -110,59 +529,72 @@ pub trait View {
 /// struct MyView;
 ///
 /// impl View for MyView {
-///     fn resume(&mut self, _: &mut Phi) {}
-///     fn pause(&mut self, _: &mut Phi) {}
-///     fn render(&mut self, context: &mut Phi, _: f64) -> ViewAction {
+///     fn update(&mut self, context: &mut Phi, _: f64) -> ViewAction {
 ///         if context.events.now.quit {
 ///             return ViewAction::Quit;
 ///         }
 ///
+///         ViewAction::None
+///     }
+///
+///     fn draw(&self, context: &mut Phi, _: f64) {
 ///         context.renderer.set_draw_color(Color::RGB(255, 255, 0));
 ///         context.renderer.clear();
-///         ViewAction::None
+///     }
+///
+///     fn enter(&mut self, _: &mut Phi) {
+///         println!("MyView is now running");
 ///     }
 /// }
 ///
-/// spawn("Example", |_| {
-///     Box::new(MyView)
-/// });
+/// spawn(WindowConfig::new("Example"), |_| {
+///     Ok(Box::new(MyView))
+/// }).unwrap();
 /// ```
-pub fn spawn<F>(title: &str, init: F)
-where F: Fn(&mut Phi) -> Box<View> {
+pub fn spawn<F>(config: WindowConfig, init: F) -> PhiResult<()>
+where F: Fn(&mut Phi) -> PhiResult<Box<dyn View>> {
     // Initialize SDL2
-    let sdl_context = ::sdl2::init().unwrap();
-    let video = sdl_context.video().unwrap();
-    let mut timer = sdl_context.timer().unwrap();
-    let _ttf_context = ::sdl2_ttf::init();
+    let sdl_context = ::sdl2::init()?;
+    let video = sdl_context.video()?;
+    let mut timer = sdl_context.timer()?;
+    let _ttf_context = ::sdl2_ttf::init()
+        .map_err(|e| PhiError::Sdl(e.to_string()))?;
 
     // Create the window
-    let window = video.window(title, 800, 600)
-        .position_centered().opengl().resizable()
-        .build().unwrap();
+    let window = config.build_window(&video)?;
+
+    let renderer =
+        if config.vsync {
+            window.renderer().accelerated().present_vsync().build()
+        } else {
+            window.renderer().accelerated().build()
+        }?;
 
     // Create the context
     let mut context = Phi::new(
-        Events::new(sdl_context.event_pump().unwrap()),
-        window.renderer()
-            .accelerated()
-            .build().unwrap());
+        Events::new(sdl_context.event_pump()?, sdl_context.game_controller().ok(), video.text_input()),
+        renderer,
+        config)?;
 
     // Create the default view
-    let mut current_view = init(&mut context);
+    let initial_view = init(&mut context)?;
+    let mut views = ViewStack::new(&mut context, initial_view);
 
 
     // Frame timing
-    let interval = 1_000 / 60;
+    let interval = (FRAME_INTERVAL * 1_000.0) as u32;
+    let frequency = timer.performance_frequency() as f64;
     let mut before = timer.ticks();
-    let mut last_second = timer.ticks();
-    let mut fps = 0u16;
+    let mut before_precise = timer.performance_counter();
+
+    // Simulated time which has not yet been consumed by `update`, in seconds.
+    let mut lag = 0.0;
 
-    loop {
+    'running: loop {
         // Frame timing (bis)
 
         let now = timer.ticks();
         let dt = now - before;
-        let elapsed = dt as f64 / 1_000.0;
 
         // If the time elapsed since the last frame is too small, wait out the
         // difference and try again.
@@ -172,28 +604,58 @@ where F: Fn(&mut Phi) -> Box<View> {
         }
 
         before = now;
-        fps += 1;
 
-        if now - last_second > 1_000 {
-            println!("FPS: {}", fps);
-            last_second = now;
-            fps = 0;
+        // `ticks` only has a millisecond resolution, which is not precise
+        // enough to accumulate simulation time.
+        let now_precise = timer.performance_counter();
+        let elapsed = (now_precise - before_precise) as f64 / frequency;
+        before_precise = now_precise;
+        lag += elapsed.min(MAX_FRAME_TIME);
+
+
+        // Logic
+
+        // Events are pumped before every step, so that a key press is seen
+        // by exactly one call to `update`.
+        let mut updates = 0;
+        while lag >= UPDATE_INTERVAL {
+            lag -= UPDATE_INTERVAL;
+            updates += 1;
+            let elapsed = context.pump_events(UPDATE_INTERVAL);
+            views.update(&mut context, elapsed);
+
+            if views.is_empty() {
+                break 'running;
+            }
         }
 
+        let updated_at = timer.performance_counter();
 
-        // Logic & rendering
 
-        context.events.pump(&mut context.renderer);
+        // Rendering
 
-        match current_view.render(&mut context, elapsed) {
-            ViewAction::None =>
-                context.renderer.present(),
+        views.draw(&mut context, lag / UPDATE_INTERVAL);
+        context.draw_stats_overlay();
+        context.renderer.present();
 
-            ViewAction::Quit =>
-                break,
+        let rendered_at = timer.performance_counter();
 
-            ViewAction::ChangeView(new_view) =>
-                current_view = new_view,
-        }
+        context.record_frame(
+            elapsed,
+            (updated_at - now_precise) as f64 / frequency,
+            updates,
+            (rendered_at - updated_at) as f64 / frequency);
     }
+
+    Ok(())
+}
+
+/// A path in the temporary directory, for the tests which write files. It is
+/// unique to the current run, so that runs do not overwrite each other's files.
+#[cfg(test)]
+pub fn temp_path(name: &str) -> String {
+    ::std::env::temp_dir()
+        .join(format!("arcade-rs-{}-{}", ::std::process::id(), name))
+        .to_string_lossy()
+        .into_owned()
 }
@@ -0,0 +1,167 @@
+use ::rand::{Rng, SeedableRng, XorShiftRng};
+use ::rand::distributions::range::SampleRange;
+
+/// The source of every random decision of the game, such as where asteroids
+/// appear. It is seeded from the seed of the run, so that the same seed and
+/// the same inputs always produce the same game.
+///
+/// The generator gives the same numbers on every platform, so that replays
+/// may be shared between players.
+#[derive(Clone, Debug)]
+pub struct Random {
+    rng: XorShiftRng,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        // Spread the bits of the seed over the whole state, so that close
+        // seeds still produce unrelated games. The state must not be zero.
+        let mut state = seed;
+        let mut words = [0; 4];
+        for word in words.iter_mut() {
+            *word = (split_mix(&mut state) >> 32) as u32;
+        }
+        if words == [0; 4] {
+            words[0] = 1;
+        }
+
+        Random {
+            rng: XorShiftRng::from_seed(words),
+        }
+    }
+
+    /// A number in `[low, high)`, for instance `range(10.0, 30.0)` or
+    /// `range(0, 4)`.
+    ///
+    /// Panics if `low >= high`.
+    pub fn range<T: PartialOrd + SampleRange>(&mut self, low: T, high: T) -> T {
+        self.rng.gen_range(low, high)
+    }
+
+    /// Returns true with the given probability, from 0 to 1.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.rng.next_f64() < probability
+    }
+
+    /// One of `items`, each of them as likely, or `None` if there is none.
+    #[cfg(test)]
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.range(0, items.len())])
+        }
+    }
+
+    /// One of `items`, picked in proportion to its weight. Items whose weight
+    /// is not positive are never picked. Returns `None` if no item can be.
+    #[cfg(test)]
+    pub fn weighted<'a, T>(&mut self, items: &'a [(T, f64)]) -> Option<&'a T> {
+        let total: f64 = items.iter()
+            .map(|&(_, weight)| weight.max(0.0))
+            .sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut left = self.rng.next_f64() * total;
+        let mut picked = None;
+        for &(ref item, weight) in items {
+            if weight <= 0.0 {
+                continue;
+            }
+            picked = Some(item);
+            if left < weight {
+                break;
+            }
+            left -= weight;
+        }
+
+        // Rounding errors may leave a little of `left`, in which case the
+        // last item is picked
+        picked
+    }
+}
+
+/// Gives access to the rest of the `rand` API, such as `gen` and `shuffle`.
+impl Rng for Random {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+}
+
+// SplitMix64, which turns a seed into well distributed words
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+        let mut c = Random::new(43);
+
+        let first: Vec<u32> = (0..16).map(|_| a.next_u32()).collect();
+        let second: Vec<u32> = (0..16).map(|_| b.next_u32()).collect();
+        let other: Vec<u32> = (0..16).map(|_| c.next_u32()).collect();
+
+        assert_eq!(first, second);
+        assert!(first != other);
+    }
+
+    #[test]
+    fn zero_seed_works() {
+        let mut rng = Random::new(0);
+        assert!((0..16).any(|_| rng.next_u32() != 0));
+    }
+
+    #[test]
+    fn range_and_chance() {
+        let mut rng = Random::new(7);
+        for _ in 0..1000 {
+            let value = rng.range(10.0, 30.0);
+            assert!((10.0..30.0).contains(&value));
+            assert!(rng.range(0, 4) < 4);
+            assert!(!rng.chance(0.0));
+            assert!(rng.chance(1.0));
+        }
+    }
+
+    #[test]
+    fn choose_from_nothing() {
+        let mut rng = Random::new(1);
+        let empty: [i32; 0] = [];
+        assert_eq!(rng.choose(&empty), None);
+        assert_eq!(rng.choose(&[5]), Some(&5));
+    }
+
+    #[test]
+    fn weighted_skips_non_positive_weights() {
+        let mut rng = Random::new(3);
+        let items = [('a', 0.0), ('b', 1.0), ('c', -2.0), ('d', 3.0), ('e', 0.0)];
+
+        let mut picked_b = 0;
+        for _ in 0..4000 {
+            match rng.weighted(&items) {
+                Some(&'b') => picked_b += 1,
+                Some(&'d') => {},
+                other => panic!("picked {:?}", other),
+            }
+        }
+
+        // About a quarter of the picks should be 'b'
+        assert!(picked_b > 800 && picked_b < 1200, "{}", picked_b);
+
+        assert_eq!(rng.weighted(&[('a', 0.0), ('b', -1.0)]), None);
+        assert_eq!(rng.weighted::<char>(&[]), None);
+    }
+}
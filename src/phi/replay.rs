@@ -0,0 +1,266 @@
+use ::phi::error::{PhiError, PhiResult};
+use ::phi::input::{mouse_from_name, mouse_name, InputEvent, InputSource};
+use ::sdl2::controller::{Axis, Button};
+use ::sdl2::keyboard::Keycode;
+use ::std::fs::File;
+use ::std::io::{Read, Write};
+
+/// The inputs received before one update step, and the time simulated by the
+/// step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub elapsed: f64,
+    pub events: Vec<InputEvent>,
+}
+
+/// The inputs of a whole run. Playing them back from the same seed reproduces
+/// the run exactly, as long as the views take their randomness from `Phi::rng`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub frames: Vec<Frame>,
+}
+
+/// Plays back the frames of a replay, one per update step.
+pub struct Playback {
+    replay: Replay,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback {
+            replay,
+            next: 0,
+        }
+    }
+}
+
+impl InputSource for Playback {
+    fn next_frame(&mut self, _elapsed: f64) -> Option<Frame> {
+        let frame = self.replay.frames.get(self.next).cloned();
+        self.next += 1;
+        frame
+    }
+}
+
+impl Replay {
+    pub fn new(seed: u64) -> Replay {
+        Replay {
+            seed,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Reads the replay saved at `path`.
+    ///
+    /// The file starts with a `seed` line. Every frame then starts with a
+    /// `frame <elapsed>` line, followed by one line per event. Empty lines and
+    /// lines starting with `#` are ignored.
+    pub fn load(path: &str) -> PhiResult<Replay> {
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|e| replay_error(path, e.to_string()))?;
+
+        let mut seed = None;
+        let mut frames: Vec<Frame> = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || replay_error(path, format!("line {}: invalid entry `{}`", number + 1, line));
+
+            let mut parts = line.splitn(2, ' ');
+            let kind = parts.next().unwrap_or("");
+            let args = parts.next().unwrap_or("").trim();
+
+            match kind {
+                "seed" => seed = Some(args.parse().map_err(|_| invalid())?),
+                "frame" => frames.push(Frame {
+                    elapsed: args.parse().map_err(|_| invalid())?,
+                    events: Vec::new(),
+                }),
+                _ => {
+                    let event = parse_event(kind, args).ok_or_else(&invalid)?;
+                    frames.last_mut().ok_or_else(&invalid)?.events.push(event);
+                },
+            }
+        }
+
+        Ok(Replay {
+            seed: seed.ok_or_else(|| replay_error(path, "missing seed".to_string()))?,
+            frames,
+        })
+    }
+
+    /// Writes the replay to `path`, in the format read by `load`.
+    pub fn save(&self, path: &str) -> PhiResult<()> {
+        let mut contents = String::from("# ArcadeRS replay\n");
+        contents.push_str(&format!("seed {}\n", self.seed));
+
+        for frame in &self.frames {
+            contents.push_str(&format!("frame {}\n", frame.elapsed));
+            for event in &frame.events {
+                contents.push_str(&format_event(event));
+                contents.push('\n');
+            }
+        }
+
+        File::create(path)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .map_err(|e| replay_error(path, e.to_string()))
+    }
+}
+
+fn format_event(event: &InputEvent) -> String {
+    match *event {
+        // Key names may contain spaces, and so come last
+        InputEvent::KeyDown { key, repeat: false } => format!("KeyDown {}", key.name()),
+        InputEvent::KeyDown { key, repeat: true } => format!("KeyRepeat {}", key.name()),
+        InputEvent::KeyUp(key) => format!("KeyUp {}", key.name()),
+        InputEvent::ButtonDown(pad, button) => format!("ButtonDown {} {}", pad, button.string()),
+        InputEvent::ButtonUp(pad, button) => format!("ButtonUp {} {}", pad, button.string()),
+        InputEvent::AxisMotion(pad, axis, value) => format!("AxisMotion {} {} {}", pad, axis.string(), value),
+        InputEvent::MouseMotion(x, y) => format!("MouseMotion {} {}", x, y),
+        InputEvent::MouseDown(button, x, y) => format!("MouseDown {} {} {}", x, y, mouse_name(button)),
+        InputEvent::MouseUp(button, x, y) => format!("MouseUp {} {} {}", x, y, mouse_name(button)),
+        InputEvent::Wheel(x, y) => format!("Wheel {} {}", x, y),
+        // Text is stored as code points, so that it may contain any character
+        InputEvent::Text(ref text) => format!("Text{}", code_points(text)),
+        InputEvent::Editing(ref text) => format!("Editing{}", code_points(text)),
+        InputEvent::FocusGained => "FocusGained".to_string(),
+        InputEvent::FocusLost => "FocusLost".to_string(),
+        InputEvent::Minimized => "Minimized".to_string(),
+        InputEvent::Restored => "Restored".to_string(),
+    }
+}
+
+fn code_points(text: &str) -> String {
+    text.chars().map(|c| format!(" {}", c as u32)).collect()
+}
+
+fn parse_event(kind: &str, args: &str) -> Option<InputEvent> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let number = |i: usize| words.get(i).and_then(|word| word.parse::<f64>().ok());
+    // The controller comes first
+    let pad = || words.first().and_then(|word| word.parse::<i32>().ok());
+
+    match kind {
+        "KeyDown" => Keycode::from_name(args).map(|key| InputEvent::KeyDown { key, repeat: false }),
+        "KeyRepeat" => Keycode::from_name(args).map(|key| InputEvent::KeyDown { key, repeat: true }),
+        "KeyUp" => Keycode::from_name(args).map(InputEvent::KeyUp),
+        "ButtonDown" | "ButtonUp" => {
+            let button = words.get(1).and_then(|word| Button::from_string(word));
+            match (pad(), button) {
+                (Some(pad), Some(button)) if kind == "ButtonDown" => Some(InputEvent::ButtonDown(pad, button)),
+                (Some(pad), Some(button)) => Some(InputEvent::ButtonUp(pad, button)),
+                _ => None,
+            }
+        },
+        "AxisMotion" => match (pad(), words.get(1).and_then(|word| Axis::from_string(word)), number(2)) {
+            (Some(pad), Some(axis), Some(value)) => Some(InputEvent::AxisMotion(pad, axis, value)),
+            _ => None,
+        },
+        "MouseMotion" => match (number(0), number(1)) {
+            (Some(x), Some(y)) => Some(InputEvent::MouseMotion(x, y)),
+            _ => None,
+        },
+        "MouseDown" | "MouseUp" => {
+            let button = words.get(2).and_then(|word| mouse_from_name(word));
+            match (button, number(0), number(1)) {
+                (Some(button), Some(x), Some(y)) if kind == "MouseDown" => Some(InputEvent::MouseDown(button, x, y)),
+                (Some(button), Some(x), Some(y)) => Some(InputEvent::MouseUp(button, x, y)),
+                _ => None,
+            }
+        },
+        "Wheel" => match (words.first().and_then(|w| w.parse().ok()), words.get(1).and_then(|w| w.parse().ok())) {
+            (Some(x), Some(y)) => Some(InputEvent::Wheel(x, y)),
+            _ => None,
+        },
+        "Text" => parse_code_points(&words).map(InputEvent::Text),
+        "Editing" => parse_code_points(&words).map(InputEvent::Editing),
+        "FocusGained" => Some(InputEvent::FocusGained),
+        "FocusLost" => Some(InputEvent::FocusLost),
+        "Minimized" => Some(InputEvent::Minimized),
+        "Restored" => Some(InputEvent::Restored),
+        _ => None,
+    }
+}
+
+fn parse_code_points(words: &[&str]) -> Option<String> {
+    words.iter()
+        .map(|word| word.parse().ok().and_then(::std::char::from_u32))
+        .collect()
+}
+
+fn replay_error(path: &str, reason: String) -> PhiError {
+    PhiError::Replay {
+        path: path.to_string(),
+        reason,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::phi::temp_path;
+    use ::sdl2::mouse::Mouse;
+    use ::std::fs;
+
+    #[test]
+    fn save_then_load() {
+        let mut replay = Replay::new(0xDEAD_BEEF_1234);
+        replay.frames.push(Frame {
+            elapsed: 1.0 / 60.0,
+            events: vec![
+                InputEvent::KeyDown { key: Keycode::Space, repeat: false },
+                InputEvent::KeyDown { key: Keycode::Space, repeat: true },
+                InputEvent::KeyUp(Keycode::Return),
+                InputEvent::ButtonDown(1, Button::A),
+                InputEvent::ButtonUp(0, Button::Start),
+                InputEvent::AxisMotion(2, Axis::LeftX, -0.123456789),
+            ],
+        });
+        replay.frames.push(Frame { elapsed: 0.02, events: vec![] });
+        replay.frames.push(Frame {
+            elapsed: 0.1,
+            events: vec![
+                InputEvent::MouseMotion(12.5, -3.25),
+                InputEvent::MouseDown(Mouse::Left, 1.0, 2.0),
+                InputEvent::MouseUp(Mouse::X2, 3.0, 4.0),
+                InputEvent::Wheel(0, -1),
+                InputEvent::Text("a b\n\u{e9}\u{1F600}".to_string()),
+                InputEvent::Editing(String::new()),
+                InputEvent::FocusLost,
+                InputEvent::FocusGained,
+                InputEvent::Minimized,
+                InputEvent::Restored,
+            ],
+        });
+
+        let path = temp_path("replay.txt");
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), replay);
+    }
+
+    #[test]
+    fn load_rejects_invalid_files() {
+        let path = temp_path("invalid-replay.txt");
+
+        for contents in &["frame 0.1\n", "seed 1\nKeyDown Space\n", "seed 1\nframe 0.1\nKeyDown NotAKey\n"] {
+            fs::write(&path, contents).unwrap();
+            assert!(Replay::load(&path).is_err(), "{:?}", contents);
+        }
+        fs::remove_file(&path).unwrap();
+
+        assert!(Replay::load(&path).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use ::std::collections::VecDeque;
+
+/// The number of frames kept to compute the percentiles of the frame time.
+const HISTORY_LEN: usize = 240;
+
+/// Statistics about how long the game takes to produce its frames. They are
+/// recorded by the game loop and exposed through `Phi::stats`.
+///
+/// All durations are expressed in seconds.
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    /// The number of frames shown during the last second.
+    pub fps: u32,
+
+    /// The duration of the last frame.
+    pub frame_time: f64,
+
+    /// The median, 95th and 99th percentiles of the duration of the last
+    /// `HISTORY_LEN` frames. They are computed once per second.
+    pub frame_time_p50: f64,
+    pub frame_time_p95: f64,
+    pub frame_time_p99: f64,
+
+    /// The time spent updating the views during the last frame, for all of
+    /// the simulation steps which it contained.
+    pub update_time: f64,
+    /// The number of simulation steps run during the last frame.
+    pub updates: u32,
+
+    /// The time spent drawing the views during the last frame.
+    pub render_time: f64,
+
+    /// The number of frames which should have been shown since the start of
+    /// the game, but were skipped because the previous ones took too long.
+    pub dropped_frames: u64,
+
+    history: VecDeque<f64>,
+    frames_this_second: u32,
+    second_elapsed: f64,
+}
+
+impl FrameStats {
+    pub fn new() -> FrameStats {
+        FrameStats {
+            fps: 0,
+            frame_time: 0.0,
+            frame_time_p50: 0.0,
+            frame_time_p95: 0.0,
+            frame_time_p99: 0.0,
+            update_time: 0.0,
+            updates: 0,
+            render_time: 0.0,
+            dropped_frames: 0,
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            frames_this_second: 0,
+            second_elapsed: 0.0,
+        }
+    }
+
+    /// Records the timing of a frame. `interval` is the duration which the
+    /// game loop aims for each frame to have.
+    ///
+    /// Returns `true` when a second has passed, so that the values which are
+    /// only computed once per second have changed.
+    pub fn record(&mut self, frame_time: f64, update_time: f64, updates: u32, render_time: f64, interval: f64) -> bool {
+        self.frame_time = frame_time;
+        self.update_time = update_time;
+        self.updates = updates;
+        self.render_time = render_time;
+
+        // A frame which lasted three intervals replaced two other frames
+        if frame_time >= interval * 2.0 {
+            self.dropped_frames += (frame_time / interval) as u64 - 1;
+        }
+
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame_time);
+
+        self.frames_this_second += 1;
+        self.second_elapsed += frame_time;
+
+        if self.second_elapsed < 1.0 {
+            return false;
+        }
+
+        self.fps = self.frames_this_second;
+        self.frames_this_second = 0;
+        self.second_elapsed -= 1.0;
+
+        let mut sorted: Vec<f64> = self.history.iter().cloned().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        self.frame_time_p50 = percentile(&sorted, 0.50);
+        self.frame_time_p95 = percentile(&sorted, 0.95);
+        self.frame_time_p99 = percentile(&sorted, 0.99);
+
+        true
+    }
+
+    /// Describes the statistics on a few lines of text, for the overlay.
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("FPS: {}", self.fps),
+            format!("Frame: {:.1} ms (p50 {:.1}, p95 {:.1}, p99 {:.1})",
+                self.frame_time * 1000.0,
+                self.frame_time_p50 * 1000.0,
+                self.frame_time_p95 * 1000.0,
+                self.frame_time_p99 * 1000.0),
+            format!("Update: {:.2} ms ({} steps)", self.update_time * 1000.0, self.updates),
+            format!("Render: {:.2} ms", self.render_time * 1000.0),
+            format!("Dropped: {}", self.dropped_frames),
+        ]
+    }
+}
+
+/// Returns the value below which a fraction `p` of the `sorted` values lie.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        let sorted: Vec<f64> = (1..101).map(|n| n as f64).collect();
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 0.50), 51.0);
+        assert_eq!(percentile(&sorted, 0.95), 95.0);
+        assert_eq!(percentile(&sorted, 0.99), 99.0);
+        assert_eq!(percentile(&sorted, 1.0), 100.0);
+
+        assert_eq!(percentile(&[4.0], 0.99), 4.0);
+        assert_eq!(percentile(&[], 0.5), 0.0);
+    }
+
+    #[test]
+    fn fps_is_updated_every_second() {
+        let mut stats = FrameStats::new();
+        assert!(!stats.record(0.25, 0.0, 1, 0.0, 0.25));
+        assert!(!stats.record(0.25, 0.0, 1, 0.0, 0.25));
+        assert!(!stats.record(0.25, 0.0, 1, 0.0, 0.25));
+        assert!(stats.record(0.25, 0.0, 1, 0.0, 0.25));
+        assert_eq!(stats.fps, 4);
+        assert_eq!(stats.frame_time_p50, 0.25);
+    }
+
+    #[test]
+    fn dropped_frames() {
+        let mut stats = FrameStats::new();
+        stats.record(0.15, 0.0, 1, 0.0, 0.1);
+        assert_eq!(stats.dropped_frames, 0);
+        // Three intervals, of which two were missed
+        stats.record(0.35, 0.0, 3, 0.0, 0.1);
+        assert_eq!(stats.dropped_frames, 2);
+    }
+
+    #[test]
+    fn percentiles_only_cover_the_last_frames() {
+        let mut stats = FrameStats::new();
+        for _ in 0..10 {
+            stats.record(0.5, 0.0, 1, 0.0, 0.5);
+        }
+        assert_eq!(stats.frame_time_p99, 0.5);
+
+        for _ in 0..HISTORY_LEN {
+            stats.record(0.25, 0.0, 1, 0.0, 0.25);
+        }
+        assert_eq!(stats.frame_time_p50, 0.25);
+        assert_eq!(stats.frame_time_p99, 0.25);
+    }
+}
@@ -0,0 +1,224 @@
+use ::sdl2::keyboard::Keycode;
+use ::sdl2::video::{FullscreenType, Window, WindowRef};
+use ::sdl2::{SdlResult, VideoSubsystem};
+use ::phi::error::PhiResult;
+
+/// The ways in which the window of the game may be displayed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowMode {
+    Windowed,
+    /// Changes the resolution of the screen to the size of the window.
+    Fullscreen,
+    /// Covers the whole screen while keeping the resolution of the desktop.
+    DesktopFullscreen,
+}
+
+impl WindowMode {
+    fn to_sdl(self) -> FullscreenType {
+        match self {
+            WindowMode::Windowed => FullscreenType::Off,
+            WindowMode::Fullscreen => FullscreenType::True,
+            WindowMode::DesktopFullscreen => FullscreenType::Desktop,
+        }
+    }
+}
+
+/// The ways in which the logical resolution of the game may be scaled to fit
+/// the window. In both cases, the aspect ratio is preserved and black bars
+/// fill the rest of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scaling {
+    /// Use the largest scale which fits in the window.
+    Fit,
+    /// Use the largest whole scale which fits in the window, so that every
+    /// logical pixel covers the same number of pixels on the screen. If the
+    /// window is smaller than the logical resolution, `Fit` is used instead.
+    Integer,
+}
+
+/// The settings used to create the window of the game. They can be changed
+/// while the game is running through `Phi::set_window_config`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub mode: WindowMode,
+    pub resizable: bool,
+    pub borderless: bool,
+
+    /// Wait for the screen to refresh before presenting a frame. This is
+    /// only read when the renderer is created, so changing it while the game
+    /// runs takes effect on the next start.
+    pub vsync: bool,
+
+    /// Use the full resolution of high-DPI ("retina") screens.
+    pub high_dpi: bool,
+
+    /// The key which switches between windowed and fullscreen, if any.
+    pub fullscreen_key: Option<Keycode>,
+
+    /// The size of the area in which the game is drawn, which is scaled to
+    /// fit in the window. If `None`, one unit is one pixel of the window.
+    pub logical_size: Option<(u32, u32)>,
+    pub scaling: Scaling,
+}
+
+impl WindowConfig {
+    /// The default configuration: a resizable 800x600 window, which can be
+    /// made fullscreen by pressing F11.
+    pub fn new(title: &str) -> WindowConfig {
+        WindowConfig {
+            title: title.to_string(),
+            width: 800,
+            height: 600,
+            mode: WindowMode::Windowed,
+            resizable: true,
+            borderless: false,
+            vsync: false,
+            high_dpi: false,
+            fullscreen_key: Some(Keycode::F11),
+            logical_size: None,
+            scaling: Scaling::Fit,
+        }
+    }
+
+    pub fn build_window(&self, video: &VideoSubsystem) -> SdlResult<Window> {
+        let mut builder = video.window(&self.title, self.width, self.height);
+        builder.position_centered().opengl();
+
+        match self.mode {
+            WindowMode::Windowed => {},
+            WindowMode::Fullscreen => { builder.fullscreen(); },
+            WindowMode::DesktopFullscreen => { builder.fullscreen_desktop(); },
+        }
+
+        if self.resizable { builder.resizable(); }
+        if self.borderless { builder.borderless(); }
+        if self.high_dpi { builder.allow_highdpi(); }
+
+        builder.build()
+    }
+
+    /// Changes the properties of `window` which differ between `self` and
+    /// `old`. Resizing the window is done by SDL, which will then notify the
+    /// views through a resize event.
+    ///
+    /// The `resizable`, `high_dpi` and `vsync` flags can only be set when the
+    /// window and its renderer are created, so they are left untouched.
+    pub fn apply(&self, old: &WindowConfig, window: &mut WindowRef) -> PhiResult<()> {
+        if self.title != old.title {
+            window.set_title(&self.title);
+        }
+
+        if self.borderless != old.borderless {
+            window.set_bordered(!self.borderless);
+        }
+
+        // Leave fullscreen before resizing the window, and enter it after, so
+        // that the windowed size is the one which is remembered
+        if self.mode != old.mode && self.mode == WindowMode::Windowed {
+            window.set_fullscreen(self.mode.to_sdl())?;
+        }
+
+        if (self.width, self.height) != (old.width, old.height) {
+            window.set_size(self.width, self.height);
+        }
+
+        if self.mode != old.mode && self.mode != WindowMode::Windowed {
+            window.set_fullscreen(self.mode.to_sdl())?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Describes where the logical area of the game lies inside of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// The size of the game area, in logical units.
+    pub size: (f64, f64),
+    /// The number of window pixels per logical unit.
+    pub scale: f64,
+    /// The position of the top-left corner of the game area, in window pixels.
+    pub offset: (f64, f64),
+}
+
+impl Viewport {
+    /// Computes the viewport of a window of size `output`, in pixels.
+    pub fn new(output: (f64, f64), logical_size: Option<(u32, u32)>, scaling: Scaling) -> Viewport {
+        let (out_w, out_h) = output;
+
+        let (w, h) = match logical_size {
+            Some((w, h)) if w > 0 && h > 0 => (w as f64, h as f64),
+            _ => return Viewport {
+                size: output,
+                scale: 1.0,
+                offset: (0.0, 0.0),
+            },
+        };
+
+        let mut scale = f64::min(out_w / w, out_h / h);
+        if scaling == Scaling::Integer && scale >= 1.0 {
+            scale = scale.floor();
+        }
+
+        Viewport {
+            size: (w, h),
+            scale,
+            offset: ((out_w - w * scale) / 2.0, (out_h - h * scale) / 2.0),
+        }
+    }
+
+    /// Converts a position in window pixels to logical units.
+    pub fn to_logical(self, pos: (f64, f64)) -> (f64, f64) {
+        ((pos.0 - self.offset.0) / self.scale,
+         (pos.1 - self.offset.1) / self.scale)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_without_logical_size() {
+        let viewport = Viewport::new((800.0, 600.0), None, Scaling::Integer);
+        assert_eq!(viewport, Viewport { size: (800.0, 600.0), scale: 1.0, offset: (0.0, 0.0) });
+
+        // A logical size without area is ignored
+        assert_eq!(Viewport::new((800.0, 600.0), Some((0, 300)), Scaling::Fit), viewport);
+    }
+
+    #[test]
+    fn viewport_fit_letterboxes() {
+        let viewport = Viewport::new((1000.0, 600.0), Some((400, 300)), Scaling::Fit);
+        assert_eq!(viewport, Viewport { size: (400.0, 300.0), scale: 2.0, offset: (100.0, 0.0) });
+
+        let viewport = Viewport::new((400.0, 400.0), Some((400, 300)), Scaling::Fit);
+        assert_eq!(viewport, Viewport { size: (400.0, 300.0), scale: 1.0, offset: (0.0, 50.0) });
+    }
+
+    #[test]
+    fn viewport_integer_scaling() {
+        let viewport = Viewport::new((1000.0, 700.0), Some((320, 240)), Scaling::Integer);
+        assert_eq!(viewport, Viewport { size: (320.0, 240.0), scale: 2.0, offset: (180.0, 110.0) });
+
+        // A window smaller than the game area falls back to `Fit`
+        let viewport = Viewport::new((200.0, 150.0), Some((400, 300)), Scaling::Integer);
+        assert_eq!(viewport, Viewport { size: (400.0, 300.0), scale: 0.5, offset: (0.0, 0.0) });
+    }
+
+    #[test]
+    fn viewport_to_logical() {
+        let viewport = Viewport::new((1000.0, 600.0), Some((400, 300)), Scaling::Fit);
+        assert_eq!(viewport.to_logical((100.0, 0.0)), (0.0, 0.0));
+        assert_eq!(viewport.to_logical((300.0, 100.0)), (100.0, 50.0));
+        assert_eq!(viewport.to_logical((900.0, 600.0)), (400.0, 300.0));
+
+        // Positions over the black bars lie outside of the game area
+        assert_eq!(viewport.to_logical((50.0, 0.0)), (-25.0, 0.0));
+    }
+}
@@ -1,5 +1,6 @@
 use ::phi::Phi;
-use ::phi::data::Rectangle;
+use ::phi::collision::Collider;
+use ::phi::data::{Rectangle, Vec2};
 use ::sdl2::pixels::Color;
 
 //? The velocity shared by all bullets, in pixels per second.
@@ -10,14 +11,19 @@ const BULLET_W: f64 = 8.0;
 const BULLET_H: f64 = 4.0;
 
 pub trait Bullet {
-    // Copy the pointer not the value it points to
-    fn update(self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<Bullet>>;
+    // Update in place. Return whether the bullet is still alive
+    fn update(&mut self, phi: &mut Phi, dt: f64) -> bool;
 
     // Render to screen
     fn render(&self, phi: &mut Phi);
 
     // Return bounding box
     fn rect(&self) -> Rectangle;
+
+    // Shape used for collisions, which is the whole rectangle by default
+    fn collider(&self) -> Collider {
+        Collider::Rect(self.rect())
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -26,28 +32,36 @@ struct RectBullet {
 }
 
 impl Bullet for RectBullet {
-    // Update bullet. If it has left the screen, None else Some(update_bullet)
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<Bullet>> {
+    // Update bullet. If it has left the screen, false else true
+    fn update(&mut self, phi: &mut Phi, dt: f64) -> bool {
         let (w, _) = phi.output_size();
         self.rect.x += BULLET_SPEED * dt;
 
         // If bullet left screen, delete it
-        if self.rect.x > w {
-            None
-        }
-        else {
-            Some(self)
-        }
+        self.rect.x <= w
     }
 
     fn render(&self, phi: &mut Phi) {
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+        if let Some(rect) = self.rect.to_sdl() {
+            phi.renderer.fill_rect(rect);
+        }
     }
 
     fn rect(&self) -> Rectangle {
         self.rect
     }
+
+    // Lengthwise, with rounded ends, as the bullet is wider than it is high
+    fn collider(&self) -> Collider {
+        let radius = self.rect.h / 2.0;
+        let y = self.rect.center().y;
+        Collider::Capsule {
+            a: Vec2::new(self.rect.left() + radius, y),
+            b: Vec2::new(self.rect.right() - radius, y),
+            radius,
+        }
+    }
 }
 
 struct SineBullet {
@@ -59,24 +73,21 @@ struct SineBullet {
 }
 
 impl Bullet for SineBullet {
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<Bullet>> {
+    fn update(&mut self, phi: &mut Phi, dt: f64) -> bool {
         self.total_time += dt;
 
         self.pos_x += BULLET_SPEED * dt;
 
         let (w, _) = phi.output_size();
 
-        if self.rect().x > w {
-            None
-        }
-        else {
-            Some(self)
-        }
+        self.rect().x <= w
     }
 
     fn render(&self, phi: &mut Phi) {
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+        if let Some(rect) = self.rect().to_sdl() {
+            phi.renderer.fill_rect(rect);
+        }
     }
 
     fn rect(&self) -> Rectangle {
@@ -99,7 +110,7 @@ struct DivergentBullet {
 }
 
 impl Bullet for DivergentBullet {
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<Bullet>> {
+    fn update(&mut self, phi: &mut Phi, dt: f64) -> bool {
         self.total_time += dt;
         self.pos_x += BULLET_SPEED * dt;
 
@@ -107,18 +118,16 @@ impl Bullet for DivergentBullet {
         let (w, h) = phi.output_size();
         let rect = self.rect();
 
-        if rect.x > w || rect.x < 0.0 ||
-           rect.y > h || rect.y < 0.0 {
-            None
-        } else {
-            Some(self)
-        }
+        rect.x <= w && rect.x >= 0.0 &&
+        rect.y <= h && rect.y >= 0.0
     }
 
     fn render(&self, phi: &mut Phi) {
         // We will render this kind of bullet in yellow.
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+        if let Some(rect) = self.rect().to_sdl() {
+            phi.renderer.fill_rect(rect);
+        }
     }
 
     fn rect(&self) -> Rectangle {
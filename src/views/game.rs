@@ -1,9 +1,15 @@
 use ::phi::{Phi, View, ViewAction};
-use ::phi::data::{MaybeAlive, Rectangle};
+use ::phi::collision::{AlphaMask, Collider};
+use ::phi::data::{Grid, Handle, Pool, Rectangle, Vec2};
+use ::phi::error::PhiResult;
+use ::phi::input::Action;
 use ::sdl2::pixels::Color;
 use ::phi::gfx::{CopySprite, Sprite, AnimatedSprite, AnimatedSpriteDescr};
+use ::phi::gfx::text::{Align, Effect, TextStyle};
 use ::views::shared::BgSet;
 use ::views::bullets::*;
+use ::std::cell::Cell;
+use ::std::rc::Rc;
 
 // Constants
 
@@ -16,6 +22,13 @@ const PLAYER_TOTAL: usize = 9;
 const PLAYER_W: f64 = 43.0;
 const PLAYER_H: f64 = 39.0;
 
+// The cannons which the player can switch between, in order
+const CANNONS: [CannonType; 3] = [
+    CannonType::RectBullet,
+    CannonType::SineBullet { amplitude: 10.0, angular_vel: 15.0 },
+    CannonType::DivergentBullet { a: 100.0, b: 1.2 },
+];
+
 // Asteroid constants
 const ASTEROID_PATH: &'static str = "assets/asteroid.png";
 const ASTEROIDS_WIDE: usize = 21;
@@ -32,6 +45,10 @@ const EXPLOSION_SIDE: f64 = 96.0;
 const EXPLOSION_FPS: f64 = 16.0;
 const EXPLOSION_DURATION: f64 = 1.0 / EXPLOSION_FPS * EXPLOSIONS_TOTAL as f64;
 
+// HUD constants
+const HUD_FONT: &str = "assets/belligerent.ttf";
+const HUD_MARGIN: f64 = 10.0;
+
 
 // Data types
 
@@ -52,14 +69,17 @@ enum PlayerFrame {
 struct Player {
     rect: Rectangle,
     sprites: Vec<Sprite>,
+    // The solid pixels of every sprite, if they could be read back
+    masks: Option<Vec<Rc<AlphaMask>>>,
     current: PlayerFrame,
-    cannon: CannonType,
+    // Index of the selected cannon in `CANNONS`
+    cannon: usize,
 }
 
 impl Player {
 
-    pub fn new(phi: &mut Phi) -> Player {
-        let spritesheet = Sprite::load(&mut phi.renderer, PLAYER_PATH).unwrap();
+    pub fn new(phi: &mut Phi) -> PhiResult<Player> {
+        let spritesheet = phi.load_sprite(PLAYER_PATH)?;
         let mut sprites = Vec::with_capacity(PLAYER_TOTAL);
 
         for y in 0..3 {
@@ -69,11 +89,18 @@ impl Player {
                     h: PLAYER_H,
                     x: PLAYER_W * x as f64,
                     y: PLAYER_H * y as f64,
-                }).unwrap());
+                })?);
             }
         }
 
-        Player {
+        // Without masks, the ship collides with a polygon which roughly follows
+        // its outline
+        let masks = sprites.iter()
+            .map(|sprite| AlphaMask::from_sprite(phi, sprite).map(Rc::new))
+            .collect::<PhiResult<Vec<_>>>()
+            .ok();
+
+        Ok(Player {
             rect: Rectangle {
                 x: 64.0,
                 y: (phi.output_size().1 - PLAYER_H) / 2.0,
@@ -81,68 +108,45 @@ impl Player {
                 h: PLAYER_H,
             },
             sprites: sprites,
+            masks,
             current: PlayerFrame::MidNorm,
-            cannon: CannonType::RectBullet,
-        }
+            cannon: 0,
+        })
     }
 
     // Event handling
     pub fn update(&mut self, phi: &mut Phi, elapsed: f64) {
         // Change player cannons
 
-        if phi.events.now.key_1 == Some(true) {
-            self.cannon = CannonType::RectBullet;
+        if phi.events.pressed(Action::Weapon1) {
+            self.cannon = 0;
         }
 
-        if phi.events.now.key_2 == Some(true) {
-            self.cannon = CannonType::SineBullet {
-                amplitude: 10.0,
-                angular_vel: 15.0,
-            };
+        if phi.events.pressed(Action::Weapon2) {
+            self.cannon = 1;
         }
 
-        if phi.events.now.key_3 == Some(true) {
-            self.cannon = CannonType::DivergentBullet {
-                a: 100.0,
-                b: 1.2,
-            };
+        if phi.events.pressed(Action::Weapon3) {
+            self.cannon = 2;
         }
 
-        // Move the Player
-
-        let diagonal =
-            (phi.events.key_up ^ phi.events.key_down) &&
-            (phi.events.key_left ^ phi.events.key_right);
+        // Tapping twice within a frame still skips two cannons
+        let next = phi.events.press_count(Action::NextWeapon);
+        self.cannon = (self.cannon + next) % CANNONS.len();
 
-        let moved =
-            if diagonal { 1.0 / 2.0f64.sqrt() }
-            else { 1.0 } * PLAYER_SPEED * elapsed;
+        // Move the Player. Sticks move the ship proportionally to how far
+        // they are pushed, while keys move it at full speed.
 
-        let dx = match(phi.events.key_left, phi.events.key_right) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
-        };
+        let direction = Vec2::new(
+            phi.events.value(Action::MoveRight) - phi.events.value(Action::MoveLeft),
+            phi.events.value(Action::MoveDown) - phi.events.value(Action::MoveUp));
 
-        let dy = match(phi.events.key_up, phi.events.key_down) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => - moved,
-            (false, true) => moved,
-        };
+        // Do not move faster diagonally
+        let moved = direction.clamp_length(1.0) * PLAYER_SPEED * elapsed;
+        let (dx, dy) = (moved.x, moved.y);
 
-        self.rect.x += dx;
-        self.rect.y += dy;
-
-        // Boundaries of the playable area
-        let movable_region = Rectangle {
-            x: 0.0,
-            y: 0.0,
-            w: phi.output_size().0 * 0.70, // don't let it go to the far right wall
-            h: phi.output_size().1,
-        };
-
-        // If player is larger than the screen, abort
-        self.rect = self.rect.move_inside(movable_region).unwrap();
+        self.rect = self.rect.translate(moved);
+        self.keep_inside(phi);
 
         // Select correct ship sprite to show
         self.current =
@@ -158,11 +162,48 @@ impl Player {
             else { unreachable!() };
     }
 
+    // Moves the ship back inside of the playable area, which changes with
+    // the size of the game area
+    pub fn keep_inside(&mut self, phi: &Phi) {
+        // Boundaries of the playable area
+        let movable_region = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            w: phi.output_size().0 * 0.70, // don't let it go to the far right wall
+            h: phi.output_size().1,
+        };
+
+        // If player is larger than the screen, keep it in the top-left corner
+        self.rect = self.rect.move_inside(movable_region)
+            .unwrap_or(Rectangle { x: 0.0, y: 0.0, ..self.rect });
+    }
+
+    pub fn collider(&self) -> Collider {
+        match self.masks {
+            Some(ref masks) => Collider::Mask {
+                mask: masks[self.current as usize].clone(),
+                rect: self.rect,
+            },
+            None => {
+                let Rectangle { x, y, w, h } = self.rect;
+                Collider::Polygon(vec![
+                    Vec2::new(x, y + h * 0.15),
+                    Vec2::new(x + w * 0.6, y + h * 0.15),
+                    Vec2::new(x + w, y + h * 0.5),
+                    Vec2::new(x + w * 0.6, y + h * 0.85),
+                    Vec2::new(x, y + h * 0.85),
+                ])
+            },
+        }
+    }
+
     pub fn render(&self, phi: &mut Phi) {
         // Debug bounding box for ship
         if DEBUG {
             phi.renderer.set_draw_color(Color::RGB(200,200,50));
-            phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+            if let Some(rect) = self.rect.to_sdl() {
+                phi.renderer.fill_rect(rect);
+            }
         }
 
         // Render ship sprite
@@ -177,108 +218,48 @@ impl Player {
         let cannon1_y = self.rect.y + 6.0;
         let cannon2_y = self.rect.y + PLAYER_H - 10.0;
 
-        spawn_bullets(self.cannon, cannons_x, cannon1_y, cannon2_y)
+        spawn_bullets(CANNONS[self.cannon], cannons_x, cannon1_y, cannon2_y)
     }
 }
 
 struct Asteroid {
     sprite: AnimatedSprite,
     rect: Rectangle,
-    vel: f64,
+    // pixels traveled every second
+    vel: Vec2,
 }
 
 impl Asteroid {
 
-    fn factory(phi: &mut Phi) -> AsteroidFactory {
+    fn factory(phi: &mut Phi) -> PhiResult<AsteroidFactory> {
         // Read asteroid sprite sheet and construct animated sprite of it
-        AsteroidFactory{
-            sprite: AnimatedSprite::with_fps(
-                AnimatedSprite::load_frames(phi, AnimatedSpriteDescr {
-                    image_path: ASTEROID_PATH,
-                    total_frames: ASTEROIDS_TOTAL,
-                    frames_high: ASTEROIDS_HIGH,
-                    frames_wide: ASTEROIDS_WIDE,
-                    frame_w: ASTEROID_SIDE,
-                    frame_h: ASTEROID_SIDE,
-                }),
-                1.0),
-        }
-    }
-
-    fn new(phi: &mut Phi) -> Asteroid {
-        let mut asteroid = Asteroid {
-            sprite: Asteroid::get_sprite(phi, 15.0),
-            rect: Rectangle {
-                w: ASTEROID_SIDE,
-                h: ASTEROID_SIDE,
-                x: 128.0,
-                y: 128.0,
-            },
-            vel: 0.0,
-        };
-
-        asteroid.reset(phi);
-        asteroid
-    }
-
-    fn reset(&mut self, phi: &mut Phi){
-        let (w,h) = phi.output_size();
-
-        // Set the fps between 10 and 30
-        // random f64 returns value between 0 and 1
-        self.sprite.set_fps(::rand::random::<f64>().abs() * 20.0 + 10.0);
-
-        self.rect = Rectangle {
-            w: ASTEROID_SIDE,
-            h: ASTEROID_SIDE,
-            x: w,
-            y: ::rand::random::<f64>().abs() * (h - ASTEROID_SIDE),
-        };
-
-        // vel between 50.0 and 150.0
-        self.vel = ::rand::random::<f64>().abs() * 100.0 + 50.0;
-    }
-
-    fn get_sprite(phi: &mut Phi, fps: f64) -> AnimatedSprite {
-        let asteroid_spritesheet = Sprite::load(&mut phi.renderer, ASTEROID_PATH).unwrap();
-        let mut asteroid_sprites = Vec::with_capacity(ASTEROIDS_TOTAL);
-
-        for yth in 0..ASTEROIDS_HIGH {
-            for xth in 0..ASTEROIDS_WIDE {
-                if ASTEROIDS_WIDE * yth + xth >= ASTEROIDS_TOTAL {
-                    break;
-                }
-
-                asteroid_sprites.push(
-                    asteroid_spritesheet.region(Rectangle {
-                        w: ASTEROID_SIDE,
-                        h: ASTEROID_SIDE,
-                        x: ASTEROID_SIDE * xth as f64,
-                        y: ASTEROID_SIDE * yth as f64,
-                    }).unwrap());
-            }
-        }
-
-        AnimatedSprite::with_fps(asteroid_sprites, fps)
+        Ok(AsteroidFactory{
+            sprite: phi.load_animation(AnimatedSpriteDescr {
+                image_path: ASTEROID_PATH,
+                total_frames: ASTEROIDS_TOTAL,
+                frames_high: ASTEROIDS_HIGH,
+                frames_wide: ASTEROIDS_WIDE,
+                frame_w: ASTEROID_SIDE,
+                frame_h: ASTEROID_SIDE,
+            }, 1.0)?,
+        })
     }
 
-    fn update(mut self, dt: f64) -> Option<Asteroid> {
-        self.rect.x -= dt * self.vel;
+    // Return whether the asteroid is still on screen
+    fn update(&mut self, dt: f64) -> bool {
+        self.rect = self.rect.translate(self.vel * dt);
         self.sprite.add_time(dt);
 
-        if self.rect.x <= -ASTEROID_SIDE {
-            None
-        }
-        else{
-            Some(self)
-        }
+        self.rect.right() > 0.0
     }
 
     fn render(&self, phi: &mut Phi) {
 
         if DEBUG {
             phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
-            phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+            if let Some(rect) = self.rect().to_sdl() {
+                phi.renderer.fill_rect(rect);
+            }
         }
 
         phi.renderer.copy_sprite(&self.sprite, self.rect);
@@ -287,6 +268,14 @@ impl Asteroid {
     fn rect(&self) -> Rectangle {
         self.rect
     }
+
+    // Asteroids are round, and do not fill the corners of their sprite
+    fn collider(&self) -> Collider {
+        Collider::Circle {
+            center: self.rect.center(),
+            radius: ASTEROID_SIDE / 2.0 * 0.8,
+        }
+    }
 }
 
 struct AsteroidFactory {
@@ -297,8 +286,9 @@ impl AsteroidFactory {
     fn random(&self, phi: &mut Phi) -> Asteroid {
         let (w,h) = phi.output_size();
 
+        // Set the fps between 10 and 30
         let mut sprite = self.sprite.clone();
-        sprite.set_fps(::rand::random::<f64>().abs() * 20.0 + 10.0);
+        sprite.set_frame_delay(1.0 / phi.rng.range(10.0, 30.0));
 
         Asteroid {
             sprite: sprite,
@@ -306,9 +296,9 @@ impl AsteroidFactory {
                 w: ASTEROID_SIDE,
                 h: ASTEROID_SIDE,
                 x: w,
-                y: ::rand::random::<f64>().abs() * (h - ASTEROID_SIDE),
+                y: phi.rng.range(0.0, 1.0) * (h - ASTEROID_SIDE),
             },
-            vel: ::rand::random::<f64>().abs() * 100.0 + 50.0,
+            vel: Vec2::new(-phi.rng.range(50.0, 150.0), 0.0),
         }
     }
 }
@@ -320,31 +310,25 @@ struct Explosion {
 }
 
 impl Explosion {
-    fn factory(phi: &mut Phi) -> ExplosionFactory {
-        ExplosionFactory{
-            sprite: AnimatedSprite::with_fps(
-                AnimatedSprite::load_frames(phi, AnimatedSpriteDescr {
-                    image_path: EXPLOSION_PATH,
-                    total_frames: EXPLOSIONS_TOTAL,
-                    frames_high: EXPLOSIONS_HIGH,
-                    frames_wide: EXPLOSIONS_WIDE,
-                    frame_w: EXPLOSION_SIDE,
-                    frame_h: EXPLOSION_SIDE,
-                }),
-                EXPLOSION_FPS),
-        }
+    fn factory(phi: &mut Phi) -> PhiResult<ExplosionFactory> {
+        Ok(ExplosionFactory{
+            sprite: phi.load_animation(AnimatedSpriteDescr {
+                image_path: EXPLOSION_PATH,
+                total_frames: EXPLOSIONS_TOTAL,
+                frames_high: EXPLOSIONS_HIGH,
+                frames_wide: EXPLOSIONS_WIDE,
+                frame_w: EXPLOSION_SIDE,
+                frame_h: EXPLOSION_SIDE,
+            }, EXPLOSION_FPS)?,
+        })
     }
 
-    fn update(mut self, dt: f64) -> Option<Explosion> {
+    // Return whether the animation is still playing
+    fn update(&mut self, dt: f64) -> bool {
         self.alive_since += dt;
         self.sprite.add_time(dt);
 
-        if self.alive_since >= EXPLOSION_DURATION {
-            None
-        }
-        else {
-            Some(self)
-        }
+        self.alive_since < EXPLOSION_DURATION
     }
 
     fn render(&self, phi: &mut Phi) {
@@ -357,7 +341,7 @@ struct ExplosionFactory {
 }
 
 impl ExplosionFactory {
-    fn at_center(&self, center: (f64,f64)) -> Explosion {
+    fn at_center(&self, center: Vec2) -> Explosion {
         let sprite = self.sprite.clone();
 
         Explosion {
@@ -376,115 +360,138 @@ impl ExplosionFactory {
 pub struct GameView{
     player: Player,
     // Store bullets behind pointers
-    bullets: Vec<Box<Bullet>>,
+    bullets: Pool<Box<dyn Bullet>>,
 
-    asteroids: Vec<Asteroid>,
+    asteroids: Pool<Asteroid>,
     asteroid_factory: AsteroidFactory,
 
-    explosions: Vec<Explosion>,
+    explosions: Pool<Explosion>,
     explosion_factory: ExplosionFactory,
 
+    // Broad phase of the collisions between bullets and asteroids
+    bullet_grid: Grid<Handle>,
+
+    // Number of asteroids destroyed by the player's bullets
+    score: u32,
+    score_style: TextStyle,
+    // Cleared if the score cannot be drawn, which `draw` cannot report
+    show_score: Cell<bool>,
+
     bg: BgSet,
 }
 
 impl GameView {
 
-    pub fn with_backgrounds(phi: &mut Phi, bg: BgSet) -> GameView {
-        GameView {
-            player: Player::new(phi),
-            bullets: vec![],
-            asteroids: vec![],
-            asteroid_factory: Asteroid::factory(phi),
-            explosions: vec![],
-            explosion_factory: Explosion::factory(phi),
+    // Load the sprites of the game ahead of time, so that starting a new game
+    // does not have to wait for the disk
+    pub fn preload(phi: &mut Phi) -> PhiResult<()> {
+        phi.assets.preload(&phi.renderer, &[PLAYER_PATH, ASTEROID_PATH, EXPLOSION_PATH])
+    }
+
+    pub fn with_backgrounds(phi: &mut Phi, bg: BgSet) -> PhiResult<GameView> {
+        Ok(GameView {
+            player: Player::new(phi)?,
+            bullets: Pool::new(),
+            asteroids: Pool::new(),
+            asteroid_factory: Asteroid::factory(phi)?,
+            explosions: Pool::new(),
+            explosion_factory: Explosion::factory(phi)?,
+            bullet_grid: Grid::new(ASTEROID_SIDE),
+            score: 0,
+            score_style: TextStyle {
+                align: Align::Right,
+                effect: Effect::Shadow { offset: (2.0, 2.0), color: Color::RGB(0, 0, 0) },
+                ..TextStyle::new(HUD_FONT, 24, Color::RGB(255, 255, 255))
+            },
+            show_score: Cell::new(true),
             bg: bg,
-        }
+        })
     }
 }
 
 impl View for GameView {
-    fn render(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
         if phi.events.now.quit {
             return ViewAction::Quit;
         }
 
-        if phi.events.now.key_escape == Some(true) {
-            return ViewAction::ChangeView(Box::new(
-                ::views::main_menu::MainMenuView::with_backgrounds(
-                    phi, self.bg.clone())));
+        if phi.events.pressed(Action::Back) {
+            return ViewAction::PopView;
         }
 
-        let old_bullets = ::std::mem::replace(&mut self.bullets, vec![]);
+        // Pause when asked to, or when the player switches to another window
+        if phi.events.pressed(Action::Pause) || phi.events.now.focus_lost || phi.events.now.minimized {
+            match ::views::pause::PauseView::new(phi) {
+                Ok(pause) => return ViewAction::PushView(Box::new(pause)),
+                Err(e) => println!("Could not pause the game: {}", e),
+            }
+        }
 
         // Update the player
         self.player.update(phi, elapsed);
 
         // Update the bullets
-        self.bullets = old_bullets.into_iter()
-            .filter_map(|bullet| bullet.update(phi, elapsed))
-            .collect();
+        self.bullets.retain(|bullet| bullet.update(phi, elapsed));
 
         // Update the asteroids
-        self.asteroids =
-            ::std::mem::replace(&mut self.asteroids, vec![])
-            .into_iter()
-            .filter_map(|asteroid| asteroid.update(elapsed))
-            .collect();
+        self.asteroids.retain(|asteroid| asteroid.update(elapsed));
 
         // Update explosions
-        self.explosions =
-            ::std::mem::replace(&mut self.explosions, vec![])
-            .into_iter()
-            .filter_map(|explosion| explosion.update(elapsed))
-            .collect();
+        self.explosions.retain(|explosion| explosion.update(elapsed));
 
         // Collision detection
 
         // Track if player is alive
         let mut player_alive = true;
+        let player_collider = self.player.collider();
 
-        // Go through bullets and wrap with MaybeAlive to track
-        let mut transition_bullets: Vec<_> =
-            ::std::mem::replace(&mut self.bullets, vec![])
-            .into_iter()
-            .map(|bullet| MaybeAlive {alive: true, value: bullet})
-            .collect();
-
-        self.asteroids =
-            ::std::mem::replace(&mut self.asteroids, vec![])
-            .into_iter()
-            .filter_map(|asteroid| {
+        // Only the bullets near an asteroid are tested against it
+        self.bullet_grid.clear();
+        for (handle, bullet) in self.bullets.iter() {
+            self.bullet_grid.insert(bullet.collider().bounds(), handle);
+        }
+
+        {
+            let bullets = &mut self.bullets;
+            let bullet_grid = &self.bullet_grid;
+            let explosions = &mut self.explosions;
+            let explosion_factory = &self.explosion_factory;
+            let score = &mut self.score;
+
+            self.asteroids.retain(|asteroid| {
                 // Default, asteroid alive
                 let mut asteroid_alive = true;
-                for bullet in &mut transition_bullets {
-                    if asteroid.rect().overlaps(bullet.value.rect()){
-                        bullet.alive = false;
+                let collider = asteroid.collider();
+                bullet_grid.query(collider.bounds(), |&handle| {
+                    // Bullets are only removed once every asteroid has been
+                    // tested, so one bullet may destroy several asteroids
+                    let hit = bullets.get(handle)
+                        .is_some_and(|bullet| collider.overlaps(&bullet.collider()));
+                    if hit {
+                        bullets.kill(handle);
                         asteroid_alive = false;
                     }
+                });
+
+                if !asteroid_alive {
+                    *score += 1;
                 }
 
-                if asteroid.rect().overlaps(self.player.rect) {
+                if collider.overlaps(&player_collider) {
                     asteroid_alive = false;
                     player_alive = false;
                 }
 
-                if asteroid_alive {
-                    Some(asteroid)
-                }
-                else {
+                if !asteroid_alive {
                     // Spawn an explosion at center of asteroid
-                    self.explosions.push(
-                        self.explosion_factory.at_center(
-                            asteroid.rect().center()));
-                    None
+                    explosions.insert(explosion_factory.at_center(asteroid.rect().center()));
                 }
-            })
-            .collect();
+                asteroid_alive
+            });
+        }
 
-        // Keep only bullets that are alive
-        self.bullets = transition_bullets.into_iter()
-            .filter_map(MaybeAlive::as_option)
-            .collect();
+        // Remove the bullets which hit an asteroid
+        self.bullets.sweep();
 
 
         // TODO
@@ -494,44 +501,68 @@ impl View for GameView {
 
         // Allow the player to shoot after the bullets are updated
         // so they spawn at the tips of the cannons
-        if phi.events.now.key_space == Some(true){
-            self.bullets.append(&mut self.player.spawn_bullets());
+        if phi.events.pressed(Action::Fire) {
+            self.bullets.extend(self.player.spawn_bullets());
         }
 
         // Random create a new asteroid about every 100 frames
-        if ::rand::random::<usize>() & 100 == 0 {
-            self.asteroids.push(self.asteroid_factory.random(phi));
+        if phi.rng.chance(0.01) {
+            self.asteroids.insert(self.asteroid_factory.random(phi));
         }
 
+        // Update the backgrounds
+        self.bg.back.update(elapsed);
+        self.bg.middle.update(elapsed);
+        self.bg.front.update(elapsed);
+
+        ViewAction::None
+    }
+
+    fn resize(&mut self, phi: &mut Phi) {
+        // The ship may now lie outside of the playable area
+        self.player.keep_inside(phi);
+    }
+
+    fn draw(&self, phi: &mut Phi, lerp: f64) {
         // Clear the screen
         phi.renderer.set_draw_color(Color::RGB(0,0,0));
         phi.renderer.clear();
 
         // Render the backgrounds
-        self.bg.back.render(&mut phi.renderer, elapsed);
-        self.bg.middle.render(&mut phi.renderer, elapsed);
+        self.bg.back.render(phi, lerp);
+        self.bg.middle.render(phi, lerp);
 
         // Render the ship
         self.player.render(phi);
 
         // Render the bullets
-        for bullet in &self.bullets {
+        for (_, bullet) in self.bullets.iter() {
             bullet.render(phi);
         }
 
         //Render the asteroids
-        for asteroid in &self.asteroids {
+        for (_, asteroid) in self.asteroids.iter() {
             asteroid.render(phi);
         }
 
         // Render the explosions
-        for explosion in &self.explosions {
+        for (_, explosion) in self.explosions.iter() {
             explosion.render(phi);
         }
 
         // Render the foreground
-        self.bg.front.render(&mut phi.renderer, elapsed);
-
-        ViewAction::None
+        self.bg.front.render(phi, lerp);
+
+        // Render the score over everything else. A missing font should not
+        // stop the game, so the score is hidden after the first error.
+        if self.show_score.get() {
+            let (win_w, win_h) = phi.output_size();
+            let hud = Rectangle::with_size(win_w, win_h).deflate(HUD_MARGIN, HUD_MARGIN);
+            let score = format!("Score: {}", self.score);
+            if let Err(e) = phi.draw_text(&score, &self.score_style, Rectangle { h: 0.0, ..hud }) {
+                println!("Could not draw the score: {}", e);
+                self.show_score.set(false);
+            }
+        }
     }
 }
@@ -1,8 +1,19 @@
 use ::phi::{Phi, View, ViewAction};
 use ::sdl2::pixels::Color;
 use ::phi::gfx::{CopySprite, Sprite};
-use ::phi::data::Rectangle;
+use ::phi::data::{Rectangle, Vec2};
+use ::phi::error::PhiResult;
+use ::phi::input;
 use ::views::shared::BgSet;
+use ::sdl2::mouse::Mouse;
+
+// Size of the box which holds the labels, and height of each label
+const BOX_W: f64 = 360.0;
+const LABEL_H: f64 = 50.0;
+
+// Holding a direction moves the selection again after a delay, in seconds
+const REPEAT_DELAY: f64 = 0.4;
+const REPEAT_INTERVAL: f64 = 0.1;
 
 struct Action {
     // Function executed if action is chosen
@@ -16,12 +27,12 @@ struct Action {
 }
 
 impl Action {
-    fn new(phi: &mut Phi, label: &'static str, func: Box<Fn(&mut Phi, BgSet) -> ViewAction>) -> Action {
-        Action {
+    fn new(phi: &mut Phi, label: &'static str, func: Box<Fn(&mut Phi, BgSet) -> ViewAction>) -> PhiResult<Action> {
+        Ok(Action {
             func: func,
-            idle_sprite: phi.ttf_str_sprite(label, "assets/belligerent.ttf", 32, Color::RGB(220,220,220)).unwrap(),
-            hover_sprite: phi.ttf_str_sprite(label, "assets/belligerent.ttf", 38, Color::RGB(255,255,255)).unwrap(),
-        }
+            idle_sprite: phi.ttf_str_sprite(label, "assets/belligerent.ttf", 32, Color::RGB(220,220,220))?,
+            hover_sprite: phi.ttf_str_sprite(label, "assets/belligerent.ttf", 38, Color::RGB(255,255,255))?,
+        })
     }
 }
 
@@ -33,42 +44,80 @@ pub struct MainMenuView{
 }
 
 impl MainMenuView {
-    pub fn new(phi: &mut Phi) -> MainMenuView {
-        let bg = BgSet::new(&mut phi.renderer);
-        MainMenuView::with_backgrounds(phi, bg)
-    }
+    pub fn new(phi: &mut Phi) -> PhiResult<MainMenuView> {
+        let bg = BgSet::new(phi)?;
+        ::views::game::GameView::preload(phi)?;
 
-    pub fn with_backgrounds(phi: &mut Phi, bg: BgSet) -> MainMenuView {
-        MainMenuView {
+        Ok(MainMenuView {
             actions: vec![
+                // The menu stays below the game, which goes back to it by
+                // popping itself off the view stack
                 Action::new(phi, "New Game", Box::new(|phi, bg| {
-                    ViewAction::ChangeView(Box::new(::views::game::GameView::with_backgrounds(phi, bg)))
-                })),
+                    match ::views::game::GameView::with_backgrounds(phi, bg) {
+                        Ok(game) => ViewAction::PushView(Box::new(game)),
+                        Err(e) => {
+                            // Stay in the menu rather than crashing
+                            println!("Could not start a new game: {}", e);
+                            ViewAction::None
+                        },
+                    }
+                }))?,
                 Action::new(phi, "Quit", Box::new(|_, _| {
                     ViewAction::Quit
-                })),
+                }))?,
             ],
             selected: 0,
             bg: bg,
+        })
+    }
+}
+
+impl MainMenuView {
+    // The area of the label at `index`, which can be hovered and clicked
+    fn label_rect(&self, phi: &Phi, index: usize) -> Rectangle {
+        let (win_w, win_h) = phi.output_size();
+        let box_h = self.actions.len() as f64 * LABEL_H;
+
+        Rectangle {
+            w: BOX_W,
+            h: LABEL_H,
+            x: (win_w - BOX_W) / 2.0,
+            y: (win_h - box_h) / 2.0 + LABEL_H * index as f64,
         }
     }
 }
 
 impl View for MainMenuView {
-    fn render(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
 
-        if phi.events.now.quit || phi.events.now.key_escape == Some(true) {
+        if phi.events.now.quit || phi.events.pressed(input::Action::Back) {
             return ViewAction::Quit;
         }
 
         // Execute currently selected option
-        if phi.events.now.key_space == Some(true) || phi.events.now.key_enter == Some(true){
+        if phi.events.pressed(input::Action::Confirm) {
             let bg = self.bg.clone();
             return (self.actions[self.selected as usize].func)(phi, bg);
         }
 
-        // Change selected option with keyboard
-        if phi.events.now.key_up == Some(true){
+        // Select the option under the cursor, and execute it when clicked
+        let mouse = Vec2::from(phi.events.mouse_position());
+        let hovered = (0..self.actions.len())
+            .find(|&i| self.label_rect(phi, i).contains_point(mouse));
+
+        if let Some(i) = hovered {
+            if phi.events.now.mouse_moved {
+                self.selected = i as i8;
+            }
+
+            if phi.events.now.mouse_pressed(Mouse::Left) {
+                let bg = self.bg.clone();
+                return (self.actions[i].func)(phi, bg);
+            }
+        }
+
+        // Change selected option
+        if phi.events.repeated(input::Action::MoveUp, REPEAT_DELAY, REPEAT_INTERVAL) {
             self.selected -= 1;
             // Wrap around
             if self.selected < 0 {
@@ -76,7 +125,7 @@ impl View for MainMenuView {
             }
         }
 
-        if phi.events.now.key_down == Some(true){
+        if phi.events.repeated(input::Action::MoveDown, REPEAT_DELAY, REPEAT_INTERVAL) {
             self.selected += 1;
             // Wrap around
             if self.selected >= self.actions.len() as i8 {
@@ -84,42 +133,50 @@ impl View for MainMenuView {
             }
         }
 
+        // Update backgrounds
+        self.bg.back.update(elapsed);
+        self.bg.middle.update(elapsed);
+        self.bg.front.update(elapsed);
+
+        ViewAction::None
+    }
+
+    fn draw(&self, phi: &mut Phi, lerp: f64) {
         // Clear screen
         phi.renderer.set_draw_color(Color::RGB(0,0,0));
         phi.renderer.clear();
 
         // Render backgrounds
-        self.bg.back.render(&mut phi.renderer, elapsed);
-        self.bg.middle.render(&mut phi.renderer, elapsed);
-        self.bg.front.render(&mut phi.renderer, elapsed);
+        self.bg.back.render(phi, lerp);
+        self.bg.middle.render(phi, lerp);
+        self.bg.front.render(phi, lerp);
 
 
         // Render labels in menu
         // Definitions for the menu's layout
         let (win_w, win_h) = phi.output_size();
-        let label_h = 50.0;
+        let label_h = LABEL_H;
         let border_width = 3.0;
-        let box_w = 360.0;
+        let box_w = BOX_W;
         let box_h = self.actions.len() as f64 * label_h;
         let margin_h = 10.0;
 
+        // The colored box which holds the labels, centered on the screen
+        let screen = Rectangle::with_size(win_w, win_h);
+        let label_box = Rectangle::with_size(box_w, box_h + margin_h * 2.0)
+            .center_at(screen.center());
+
         // Render the border of the colored box which holds the labels
         phi.renderer.set_draw_color(Color::RGB(70, 15, 70));
-        phi.renderer.fill_rect(Rectangle {
-            w: box_w + border_width * 2.0,
-            h: box_h + border_width * 2.0 + margin_h * 2.0,
-            x: (win_w - box_w) / 2.0 - border_width,
-            y: (win_h - box_h) / 2.0 - margin_h - border_width,
-        }.to_sdl().unwrap());
+        if let Some(rect) = label_box.inflate(border_width, border_width).to_sdl_clipped(screen) {
+            phi.renderer.fill_rect(rect);
+        }
 
         // Render the colored box which holds the labels
         phi.renderer.set_draw_color(Color::RGB(140, 30, 140));
-        phi.renderer.fill_rect(Rectangle {
-            w: box_w,
-            h: box_h + margin_h * 2.0,
-            x: (win_w - box_w) / 2.0,
-            y: (win_h - box_h) / 2.0 - margin_h,
-        }.to_sdl().unwrap());
+        if let Some(rect) = label_box.to_sdl_clipped(screen) {
+            phi.renderer.fill_rect(rect);
+        }
 
         // Render the labels in the menu
         for (i, action) in self.actions.iter().enumerate() {
@@ -141,7 +198,40 @@ impl View for MainMenuView {
                 });
             }
         }
+    }
+}
 
-        ViewAction::None
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::phi::UPDATE_INTERVAL;
+    use ::phi::headless::Headless;
+    use ::phi::input::Script;
+    use ::sdl2::keyboard::Keycode;
+
+    #[test]
+    fn play_through_the_main_menu() {
+        let mut headless = Headless::new(800, 600, |phi| {
+            Ok(Box::new(MainMenuView::new(phi)?))
+        }).unwrap();
+
+        let script = Script::new()
+            .wait(2)
+            // Start a new game, which is pushed over the menu
+            .tap(Keycode::Return)
+            .wait(60)
+            // Go back to the menu
+            .tap(Keycode::Escape)
+            .wait(2)
+            // Select "Quit", then confirm
+            .tap(Keycode::Down)
+            .tap(Keycode::Return);
+        assert_eq!(script.len(), 72);
+        headless.phi.events.set_source(Box::new(script));
+
+        // The game quits on the step which confirms "Quit"
+        assert!(headless.step(70, UPDATE_INTERVAL));
+        assert!(!headless.step(1, UPDATE_INTERVAL));
     }
 }
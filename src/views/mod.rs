@@ -2,3 +2,4 @@ pub mod game;
 pub mod main_menu;
 pub mod shared;
 pub mod bullets;
+pub mod pause;
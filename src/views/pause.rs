@@ -0,0 +1,81 @@
+use ::phi::{Phi, View, ViewAction};
+use ::sdl2::pixels::Color;
+use ::sdl2::render::BlendMode;
+use ::phi::gfx::text::{Align, Effect, TextStyle};
+use ::phi::data::Rectangle;
+use ::phi::error::PhiResult;
+use ::phi::input::Action;
+use ::std::cell::Cell;
+
+const LABEL: &str = "Paused";
+
+// Shown on top of the game, which stays frozen underneath until the player
+// resumes it
+pub struct PauseView {
+    style: TextStyle,
+    // Height of the label, so that it can be centered vertically
+    label_h: f64,
+    // Cleared if the label cannot be drawn, which `draw` cannot report
+    show_label: Cell<bool>,
+}
+
+impl PauseView {
+    pub fn new(phi: &mut Phi) -> PhiResult<PauseView> {
+        let style = TextStyle {
+            align: Align::Center,
+            effect: Effect::Outline { width: 2.0, color: Color::RGB(0, 0, 0) },
+            ..TextStyle::new("assets/belligerent.ttf", 48, Color::RGB(255, 255, 255))
+        };
+        let (_, label_h) = phi.measure_text(LABEL, &style, None)?;
+
+        Ok(PauseView {
+            style,
+            label_h,
+            show_label: Cell::new(true),
+        })
+    }
+}
+
+impl View for PauseView {
+    fn update(&mut self, phi: &mut Phi, _: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        // Go back to the game
+        if phi.events.pressed(Action::Back) || phi.events.pressed(Action::Pause) {
+            return ViewAction::PopView;
+        }
+
+        ViewAction::None
+    }
+
+    fn draw(&self, phi: &mut Phi, _: f64) {
+        let (win_w, win_h) = phi.output_size();
+
+        // Darken the game below
+        phi.renderer.set_blend_mode(BlendMode::Blend);
+        phi.renderer.set_draw_color(Color::RGBA(0, 0, 0, 160));
+        if let Some(rect) = Rectangle::with_size(win_w, win_h).to_sdl() {
+            phi.renderer.fill_rect(rect);
+        }
+        phi.renderer.set_blend_mode(BlendMode::None);
+
+        if self.show_label.get() {
+            let dest = Rectangle {
+                w: win_w,
+                h: self.label_h,
+                x: 0.0,
+                y: (win_h - self.label_h) / 2.0,
+            };
+            if let Err(e) = phi.draw_text(LABEL, &self.style, dest) {
+                println!("Could not draw the pause label: {}", e);
+                self.show_label.set(false);
+            }
+        }
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+}
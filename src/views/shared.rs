@@ -1,6 +1,7 @@
-use ::sdl2::render::Renderer;
 use :: phi::data::Rectangle;
 use ::phi::gfx::{CopySprite, Sprite};
+use ::phi::{Phi, UPDATE_INTERVAL};
+use ::phi::error::PhiResult;
 
 #[derive(Clone)]
 pub struct Background {
@@ -11,28 +12,35 @@ pub struct Background {
 }
 
 impl Background {
-    pub fn render(&mut self, renderer: &mut Renderer, elapsed: f64) {
+    pub fn update(&mut self, elapsed: f64) {
         // Determine position based on time and image dimension, not screen size
         let size = self.sprite.size();
         self.pos += self.vel * elapsed;
         if self.pos > size.0 {
             self.pos -= size.0;
         }
+    }
+
+    pub fn render(&self, phi: &mut Phi, lerp: f64) {
+        let size = self.sprite.size();
 
-        // Determine scale ratio of window to sprite
-        let (win_w, win_h) = renderer.output_size().unwrap();
-        let scale = win_h as f64 / size.1;
+        // Interpolate the position between the last two simulation steps
+        let pos = self.pos + self.vel * lerp * UPDATE_INTERVAL;
+
+        // Determine scale ratio of game area to sprite
+        let (win_w, win_h) = phi.output_size();
+        let scale = win_h / size.1;
 
         // Render as many copies of background as necessary to fill screen
-        let mut physical_left = -self.pos * scale;
+        let mut physical_left = -pos * scale;
 
-        while physical_left < win_w as f64 {
+        while physical_left < win_w {
             // While left of image is still inside window
-            renderer.copy_sprite(&self.sprite, Rectangle {
+            phi.renderer.copy_sprite(&self.sprite, Rectangle {
                 x: physical_left,
                 y: 0.0,
                 w: size.0 * scale,
-                h: win_h as f64,
+                h: win_h,
             });
 
             physical_left += size.0 * scale;
@@ -48,23 +56,23 @@ pub struct BgSet {
 }
 
 impl BgSet {
-    pub fn new(renderer: &mut Renderer) -> BgSet {
-        BgSet{
+    pub fn new(phi: &mut Phi) -> PhiResult<BgSet> {
+        Ok(BgSet{
             back: Background {
                 pos: 0.0,
                 vel: 20.0,
-                sprite: Sprite::load(renderer, "assets/starBG.png").unwrap(),
+                sprite: phi.load_sprite("assets/starBG.png")?,
             },
             middle: Background {
                 pos: 0.0,
                 vel: 40.0,
-                sprite: Sprite::load(renderer, "assets/starMG.png").unwrap(),
+                sprite: phi.load_sprite("assets/starMG.png")?,
             },
             front: Background {
                 pos: 0.0,
                 vel: 80.0,
-                sprite: Sprite::load(renderer, "assets/starFG.png").unwrap(),
+                sprite: phi.load_sprite("assets/starFG.png")?,
             },
-        }
+        })
     }
 }